@@ -0,0 +1,70 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+
+use day5::{location_for_seed, lowest_location, lowest_location_ranges, parse_almanac_str, seed_ranges, BruteForceSolver, IntervalSolver, LocationScanSolver, Num, PreparedAlmanac, Solver};
+
+const MAP_HEADERS: [&str; 7] = [
+    "seed-to-soil map:",
+    "soil-to-fertilizer map:",
+    "fertilizer-to-water map:",
+    "water-to-light map:",
+    "light-to-temperature map:",
+    "temperature-to-humidity map:",
+    "humidity-to-location map:"
+];
+
+// 7 maps of 1429 ranges each (~10k mappings). Sources are laid out in order with small gaps so there's identity
+// fall-through to exercise, and destinations are a fixed per-map permutation of the same slots.
+fn generate_almanac() -> String {
+    const RANGES_PER_MAP: Num = 1429;
+    let mut input = String::from("seeds:");
+
+    for i in 0..10 {
+        input.push_str(&format!(" {} {}", i * 12_345_678 % 142_900, 5_000 + i * 1_000));
+    }
+    input.push('\n');
+
+    for (map, header) in MAP_HEADERS.iter().enumerate() {
+        input.push_str(&format!("\n{}\n", header));
+
+        for i in 0..RANGES_PER_MAP {
+            let slot = (i * 7919 + map as Num * 31) % RANGES_PER_MAP;
+            input.push_str(&format!("{} {} 90\n", slot * 100, i * 100));
+        }
+    }
+
+    input
+}
+
+fn bench_solve(c: &mut Criterion) {
+    let input = generate_almanac();
+    let (seeds, almanac) = parse_almanac_str(&input).unwrap();
+    let seed_ranges = seed_ranges(&seeds);
+
+    c.bench_function("parse_almanac_str", |b| b.iter(|| parse_almanac_str(&input).unwrap()));
+    c.bench_function("part 1", |b| b.iter(|| lowest_location(&seeds, &almanac)));
+    c.bench_function("part 2", |b| b.iter(|| lowest_location_ranges(&seed_ranges, &almanac)));
+
+    // Brute-force part 2 over the first seed range: scanning for each map vs looking it up in a PreparedAlmanac
+    let (start, len) = seed_ranges[0];
+    let prepared = PreparedAlmanac::new(&almanac);
+    c.bench_function("part 2 brute force", |b| b.iter(|| (start..start + len).map(|seed| location_for_seed(seed, &almanac)).min()));
+    c.bench_function("part 2 brute force, prepared", |b| b.iter(|| (start..start + len).map(|seed| prepared.location_for_seed(seed)).min()));
+
+    // Every Solver on the full seed ranges
+    let solvers: [(&str, &dyn Solver); 3] = [("brute force", &BruteForceSolver), ("interval", &IntervalSolver), ("location scan", &LocationScanSolver)];
+    for (name, solver) in solvers {
+        c.bench_function(&format!("part 2, {} solver", name), |b| b.iter(|| solver.solve(&seed_ranges, &almanac)));
+    }
+
+    // The interval solver again, on randomly shuffled maps instead of the fixed permutation above
+    #[cfg(feature = "random")]
+    {
+        use rand::{rngs::StdRng, SeedableRng};
+
+        let random = day5::random_almanac(&mut StdRng::seed_from_u64(5), 1429);
+        c.bench_function("part 2, random almanac", |b| b.iter(|| lowest_location_ranges(&seed_ranges, &random)));
+    }
+}
+
+criterion_group!(benches, bench_solve);
+criterion_main!(benches);
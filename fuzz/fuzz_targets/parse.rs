@@ -0,0 +1,11 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+// Any input at all has to come back as Ok or an AlmanacError -- a panic anywhere in the parser is a bug
+fuzz_target!(|data: &[u8]| {
+    let input = String::from_utf8_lossy(data);
+
+    let _ = day5::parse_almanac_str(&input);
+    let _ = day5::parse_almanac_str_strict(&input);
+});
@@ -0,0 +1,1394 @@
+// Almanacs and everything around them that needs std: parsing, file I/O and the solvers
+
+use std::{cmp::Ordering, collections::{hash_map::Entry, BTreeMap, HashMap}, error::Error, fmt, fs, hash::Hash, io::{self, BufRead, Seek, SeekFrom}, mem::{discriminant, Discriminant}, path::Path, str::FromStr};
+
+use crate::{get_resource_num, intersect, map_coverage, resource_name, resource_position, to_next_resource, to_previous_resource, try_to_previous_resource, FarmMapping, Num, Resource, RESOURCE_ORDER};
+
+/// Every map in the almanac, keyed by its (source, destination) resources. Only the variants of the key matter.
+pub type ConversionTable = HashMap<(Resource, Resource), Vec<FarmMapping>>;
+
+/// A parsed set of maps. Each map is kept sorted by `dest_start`, which backward conversion relies on.
+#[derive(Clone)]
+#[derive(Default)]
+#[derive(PartialEq)]
+#[derive(Eq)]
+#[derive(Debug)]
+pub struct Almanac {
+    maps: ConversionTable,
+    allow_negative: bool        // Negative resource numbers are real, not a sign of a malformed mapping
+}
+
+impl Almanac {
+    pub fn new() -> Almanac {
+        Almanac::default()
+    }
+
+    /// Read-only view of the underlying maps
+    pub fn maps(&self) -> &ConversionTable {
+        &self.maps
+    }
+
+    /// Every map with its (src, dest) key, in Seed -> Location order rather than the table's arbitrary one
+    pub fn maps_in_chain_order(&self) -> Vec<(&(Resource, Resource), &Vec<FarmMapping>)> {
+        let mut maps = self.maps.iter().collect::<Vec<_>>();
+        maps.sort_by_key(|((src, _dest), _mappings)| resource_position(src));
+        maps
+    }
+
+    /// Whether negative numbers count as valid resources (see [`ParseOptions::allow_negative`])
+    pub fn allows_negative(&self) -> bool {
+        self.allow_negative
+    }
+
+    /// The same maps with negative numbers allowed or not
+    pub fn with_negative_allowed(self, allow_negative: bool) -> Almanac {
+        Almanac { allow_negative, ..self }
+    }
+
+    /// How much got parsed. A complete puzzle input has 7 maps.
+    pub fn stats(&self) -> AlmanacStats {
+        AlmanacStats {
+            maps: self.maps.len(),
+            total_ranges: self.maps.values().map(|mappings| mappings.len()).sum()
+        }
+    }
+
+    /// Checks that all seven maps of the standard chain are there, listing the (src, dest) key of each one that isn't.
+    /// A mistyped header leaves its map missing rather than failing the parse, so this is worth running on puzzle
+    /// input.
+    pub fn assert_complete(&self) -> Result<(), Vec<(Resource, Resource)>> {
+        let missing = RESOURCE_ORDER.windows(2)
+                                    .map(|stages| (stages[0](0), stages[1](0)))
+                                    .filter(|(src, dest)| !self.maps.keys().any(|(r_src, r_dest)| discriminant(src) == discriminant(r_src) && discriminant(dest) == discriminant(r_dest)))
+                                    .collect::<Vec<(Resource, Resource)>>();
+
+        if missing.is_empty() {
+            return Ok(());
+        }
+
+        Err(missing)
+    }
+
+    /// Every structural check in one pass: maps missing from the standard chain (see [`Almanac::assert_complete`]),
+    /// every pair of overlapping source ranges (where [`validate_almanac`] stops at the first)
+    /// and mappings with negative numbers (unless [`Almanac::allows_negative`]). Malformed lines never get this far --
+    /// they fail the parse.
+    pub fn validate_all(&self) -> Result<(), Vec<ValidationIssue>> {
+        let mut issues = self.assert_complete()
+                             .err()
+                             .unwrap_or_default()
+                             .into_iter()
+                             .map(|(src, dest)| ValidationIssue::MissingMap(src, dest))
+                             .collect::<Vec<ValidationIssue>>();
+
+        let maps = self.maps_in_chain_order();
+
+        for ((src, _dest), mappings) in maps {
+            let mut by_src = mappings.clone();
+            by_src.sort_by_key(|mapping| mapping.src_start);
+
+            // One mapping can overlap several later ones, not just its neighbour, so each is checked against every
+            // mapping that starts before it ends
+            for (index, first) in by_src.iter().enumerate() {
+                let end = first.src_start.saturating_add(first.range);
+
+                for second in by_src[index + 1..].iter().take_while(|second| second.src_start < end) {
+                    if first.overlaps_src(second) {
+                        issues.push(ValidationIssue::Overlap(*src, *first, *second));
+                    }
+                }
+            }
+
+            // A negative range is malformed either way
+            let negative = |mapping: &&FarmMapping| mapping.range < 0 || (!self.allow_negative && (mapping.dest_start < 0 || mapping.src_start < 0));
+            for mapping in by_src.iter().filter(negative) {
+                issues.push(ValidationIssue::Negative(*src, *mapping));
+            }
+        }
+
+        if issues.is_empty() {
+            return Ok(());
+        }
+
+        Err(issues)
+    }
+
+    /// The same maps pointing the other way; see [`InvertedAlmanac`]
+    pub fn inverted(&self) -> InvertedAlmanac {
+        InvertedAlmanac { maps: swap_maps(&self.maps), allow_negative: self.allow_negative }
+    }
+
+    /// Converts one step towards Location; see [`convert_resource_forward`]
+    pub fn convert_forward(&self, resource: Resource) -> Resource {
+        convert_resource_forward(resource, self)
+    }
+
+    /// Converts one step towards Seed; see [`convert_resource`]. A missing map passes the number through unchanged,
+    /// but a negative result is still an error.
+    pub fn convert_backward(&self, resource: Resource) -> Result<Resource, Resource> {
+        convert(resource, self, Direction::Backward)
+    }
+
+    /// See [`lowest_location`]
+    pub fn lowest_location(&self, seeds: &[Num]) -> Num {
+        lowest_location(seeds, self)
+    }
+
+    /// Drops every identity mapping (`dest_start == src_start`), since numbers outside all mappings pass through
+    /// unchanged anyway
+    pub fn optimize(&mut self) {
+        for mappings in self.maps.values_mut() {
+            mappings.retain(|mapping| mapping.dest_start != mapping.src_start);
+        }
+    }
+
+    /// Merges neighbouring mappings that are really one continuous translation: contiguous source ranges shifted
+    /// by the same offset. Exact duplicates are dropped, and two mappings whose combined range wouldn't fit in a
+    /// `Num` are left apart.
+    pub fn coalesce(&mut self) {
+        for mappings in self.maps.values_mut() {
+            // Sorting on every field puts duplicates next to each other
+            mappings.sort_by_key(|mapping| (mapping.src_start, mapping.dest_start, mapping.range));
+
+            let mut merged: Vec<FarmMapping> = Vec::with_capacity(mappings.len());
+            for mapping in mappings.drain(..) {
+                match merged.last_mut() {
+                    Some(last) if *last == mapping => {}
+                    Some(last) if last.src_start.saturating_add(last.range) == mapping.src_start
+                               && last.dest_start.saturating_add(last.range) == mapping.dest_start
+                               && last.range.checked_add(mapping.range).is_some() => last.range += mapping.range,
+                    _ => merged.push(mapping)
+                }
+            }
+
+            // Back to sorted-by-destination for backward conversion
+            merged.sort_by_key(|mapping| mapping.dest_start);
+            *mappings = merged;
+        }
+    }
+}
+
+/// An almanac turned around by [`Almanac::inverted`]: every key becomes (dest, src) and every mapping swaps its
+/// starts, so converting forward here walks from Location towards Seed. It's a type of its own because nothing
+/// [`Almanac`] does makes sense on maps pointing that way.
+#[derive(Clone)]
+#[derive(PartialEq)]
+#[derive(Eq)]
+#[derive(Debug)]
+pub struct InvertedAlmanac {
+    maps: ConversionTable,
+    allow_negative: bool
+}
+
+impl InvertedAlmanac {
+    /// Read-only view of the swapped maps, keyed (dest, src) of the original
+    pub fn maps(&self) -> &ConversionTable {
+        &self.maps
+    }
+
+    /// Converts one step towards Seed through the swapped map leaving the resource, with the same result as
+    /// [`Almanac::convert_backward`] on the original: a missing map passes the number through, and a negative
+    /// result fails unless the original allowed negatives.
+    pub fn convert_forward(&self, resource: Resource) -> Result<Resource, Resource> {
+        let resource_num = *get_resource_num(&resource);
+        let converted = self.maps.iter()
+                                 .find(|((r_src, _r_dest), _mappings)| discriminant(&resource) == discriminant(r_src))
+                                 .and_then(|(_key, mappings)| mappings.iter().find(|mapping| mapping.contains_src(resource_num)))
+                                 .map(|mapping| mapping.apply_forward(resource_num));
+
+        reject_negative(resource, to_previous_resource(resource, converted), self.allow_negative)
+    }
+
+    /// Turns the maps back around into the original almanac
+    pub fn inverted(&self) -> Almanac {
+        Almanac::try_from(swap_maps(&self.maps)).expect("swapping back gives the original, valid, maps")
+                                                .with_negative_allowed(self.allow_negative)
+    }
+}
+
+// Swaps every key to (dest, src) and every mapping's starts, which is its own inverse
+fn swap_maps(maps: &ConversionTable) -> ConversionTable {
+    maps.iter()
+        .map(|((src, dest), mappings)| {
+            let swapped = mappings.iter()
+                                  .map(|mapping| FarmMapping { dest_start: mapping.src_start, src_start: mapping.dest_start, range: mapping.range })
+                                  .collect();
+            ((*dest, *src), swapped)
+        })
+        .collect()
+}
+
+/// A problem found by [`Almanac::validate_all`]. Map-specific issues carry the zero-numbered source resource of
+/// their map.
+#[derive(Clone)]
+#[derive(Copy)]
+#[derive(PartialEq)]
+#[derive(Eq)]
+#[derive(Debug)]
+pub enum ValidationIssue {
+    MissingMap(Resource, Resource),
+    Overlap(Resource, FarmMapping, FarmMapping),
+    Negative(Resource, FarmMapping)
+}
+
+impl fmt::Display for ValidationIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ValidationIssue::MissingMap(src, dest)              => write!(f, "No {}-to-{} map", resource_name(src), resource_name(dest)),
+            ValidationIssue::Overlap(src, first, second)        => write!(f, "Overlapping {} mappings \"{}\" and \"{}\"", resource_name(src), first, second),
+            ValidationIssue::Negative(src, mapping)             => write!(f, "Negative number in {} mapping \"{}\"", resource_name(src), mapping)
+        }
+    }
+}
+
+/// Summary counts from [`Almanac::stats`]
+#[derive(Clone)]
+#[derive(Copy)]
+#[derive(PartialEq)]
+#[derive(Eq)]
+#[derive(Debug)]
+pub struct AlmanacStats {
+    pub maps: usize,
+    pub total_ranges: usize     // Mapping lines across every map
+}
+
+/// Builds an almanac from a bare table. Only the variants of each key matter, so a key has to join neighbouring
+/// stages (else `UnknownMap`) and no two keys can name the same map (else `DuplicateMap`) -- two maps out of one
+/// stage would leave conversion to pick between them in HashMap order. Checking here means no lookup ever has to.
+impl TryFrom<ConversionTable> for Almanac {
+    type Error = AlmanacError;
+
+    fn try_from(table: ConversionTable) -> Result<Almanac, AlmanacError> {
+        let mut entries = table.into_iter().collect::<Vec<_>>();
+        entries.sort_by_key(|(key, _mappings)| *key);      // So the error reported doesn't depend on HashMap order
+
+        let mut maps: ConversionTable = HashMap::with_capacity(entries.len());
+        for ((src, dest), mut mappings) in entries {
+            let key = (RESOURCE_ORDER[resource_position(&src)](0), RESOURCE_ORDER[resource_position(&dest)](0));
+            let header = || format!("{}-to-{}", resource_name(&src), resource_name(&dest));
+
+            if resource_position(&dest) != resource_position(&src) + 1 {
+                return Err(AlmanacError::UnknownMap(header()));
+            }
+
+            // Establish the sorted-by-destination invariant so backward conversion can binary search
+            mappings.sort_by_key(|mapping| mapping.dest_start);
+
+            match maps.entry(key) {
+                Entry::Occupied(_)  => return Err(AlmanacError::DuplicateMap(header())),
+                Entry::Vacant(slot) => { slot.insert(mappings); }
+            }
+        }
+
+        Ok(Almanac { maps, allow_negative: false })
+    }
+}
+
+/// Parses almanac text, discarding the seeds line. (A `TryFrom` for the `(seeds, almanac)` pair isn't possible, since
+/// neither the tuple nor `&str` is local to this crate -- use [`parse_almanac_str`] for that.)
+impl TryFrom<&str> for Almanac {
+    type Error = AlmanacError;
+
+    fn try_from(input: &str) -> Result<Almanac, AlmanacError> {
+        parse_almanac_str(input).map(|(_seeds, almanac)| almanac)
+    }
+}
+
+/// Builds an [`Almanac`] map by map, without going through the text format:
+///
+/// ```
+/// use day5::{AlmanacBuilder, Resource};
+///
+/// let almanac = AlmanacBuilder::new().map(Resource::Seed, Resource::Soil, &[(50, 98, 2), (52, 50, 48)])
+///                                    .build()
+///                                    .unwrap();
+///
+/// assert_eq!(almanac.convert_forward(Resource::Seed(79)), Resource::Soil(81));
+/// ```
+#[derive(Default)]
+pub struct AlmanacBuilder {
+    maps: ConversionTable,
+    error: Option<AlmanacError>     // The first problem spotted, reported by build()
+}
+
+impl AlmanacBuilder {
+    pub fn new() -> AlmanacBuilder {
+        AlmanacBuilder::default()
+    }
+
+    /// Adds the map from `src` to `dest`, given as (dest_start, src_start, range) triples like the input lines.
+    /// Stages have to be neighbours in the chain, and each map can only be given once.
+    pub fn map(mut self, src: fn(Num) -> Resource, dest: fn(Num) -> Resource, mappings: &[(Num, Num, Num)]) -> AlmanacBuilder {
+        let key = (src(0), dest(0));
+        let header = || format!("{}-to-{}", resource_name(&key.0), resource_name(&key.1));
+
+        if self.error.is_none() && resource_position(&key.1) != resource_position(&key.0) + 1 {
+            self.error = Some(AlmanacError::UnknownMap(header()));
+        }
+
+        if self.error.is_none() {
+            match self.maps.entry(key) {
+                Entry::Occupied(_)  => self.error = Some(AlmanacError::DuplicateMap(header())),
+                Entry::Vacant(slot) => {
+                    slot.insert(mappings.iter()
+                                        .map(|(dest_start, src_start, range)| FarmMapping { dest_start: *dest_start, src_start: *src_start, range: *range })
+                                        .collect());
+                }
+            }
+        }
+
+        self
+    }
+
+    pub fn build(self) -> Result<Almanac, AlmanacError> {
+        match self.error {
+            Some(e) => Err(e),
+            None    => Almanac::try_from(self.maps)
+        }
+    }
+}
+
+/// How forgiving the parser is. The default matches [`parse_almanac_str`].
+#[derive(Clone)]
+#[derive(Copy)]
+#[derive(PartialEq)]
+#[derive(Eq)]
+#[derive(Debug)]
+pub struct ParseOptions<'a> {
+    pub strict: bool,               // Fail on seeds that aren't numbers rather than dropping them
+    pub range_format: RangeFormat,  // How to read the third number on each mapping line
+    pub skip_bad_lines: bool,       // Skip malformed mapping lines (see parse_almanac_str_lenient) instead of failing
+    pub seeds_keyword: &'a str,     // Label on the seeds line, for variants that call them something else
+    pub allow_negative: bool        // Treat negative seeds and mappings as valid rather than malformed
+}
+
+impl Default for ParseOptions<'_> {
+    fn default() -> Self {
+        ParseOptions { strict: false, range_format: RangeFormat::default(), skip_bad_lines: false, seeds_keyword: SEEDS_KEYWORD, allow_negative: false }
+    }
+}
+
+/// The label the puzzle puts in front of its seeds
+pub const SEEDS_KEYWORD: &str = "seeds:";
+
+/// What the third number of a mapping line means
+#[derive(Clone)]
+#[derive(Copy)]
+#[derive(Default)]
+#[derive(PartialEq)]
+#[derive(Eq)]
+#[derive(Debug)]
+pub enum RangeFormat {
+    #[default]
+    Length,     // "dest src length", as in the puzzle
+    End         // "dest src end", where end is the last source number covered
+}
+
+/// Reads the names `--range-format` takes, "length" or "end"
+impl FromStr for RangeFormat {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<RangeFormat, String> {
+        match name {
+            "length"    => Ok(RangeFormat::Length),
+            "end"       => Ok(RangeFormat::End),
+            _           => Err(format!("Unknown range format {:?}", name))
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum AlmanacError {
+    FileOpen(io::Error),
+    BadMapping { line: usize, content: String },
+    BadSeeds { line: usize, content: String },
+    UnknownMap(String),
+    DuplicateMap(String),
+    NoSeeds,
+    InPart { part: usize, error: Box<AlmanacError> },  // Something wrong within one of several inputs, by index
+    #[cfg(feature = "http")]
+    Fetch(String)
+}
+
+impl fmt::Display for AlmanacError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AlmanacError::FileOpen(e)                   => write!(f, "Unable to open file: {}", e),
+            AlmanacError::BadMapping { line, content }  => write!(f, "Malformed mapping on line {}: {:?}", line, content),
+            AlmanacError::BadSeeds { line, content }    => write!(f, "Unable to parse seeds on line {}: {:?}", line, content),
+            AlmanacError::UnknownMap(header)            => write!(f, "Unknown map header {:?}", header),
+            AlmanacError::DuplicateMap(header)          => write!(f, "Map {:?} given more than once", header),
+            AlmanacError::NoSeeds                       => write!(f, "No seeds line found"),
+            AlmanacError::InPart { part, error }        => write!(f, "In input {}: {}", part + 1, error),
+            #[cfg(feature = "http")]
+            AlmanacError::Fetch(e)                      => write!(f, "Unable to download almanac: {}", e)
+        }
+    }
+}
+
+impl Error for AlmanacError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            AlmanacError::FileOpen(e) => Some(e),
+            AlmanacError::InPart { error, .. } => Some(error),
+            _ => None
+        }
+    }
+}
+
+/// Why a map lookup didn't produce exactly one map. Building an [`Almanac`] already rules out two maps for one
+/// stage, so `Ambiguous` is only a backstop.
+#[derive(Clone)]
+#[derive(Copy)]
+#[derive(PartialEq)]
+#[derive(Eq)]
+#[derive(Debug)]
+pub enum LookupError {
+    NotFound,
+    Ambiguous(usize)    // How many maps matched
+}
+
+// The vec reference should live as long as the almanac does
+pub fn find_mappings_for_dest_resource<'a>(resource: &Resource, conversion_table: &'a Almanac) -> Result<&'a Vec<FarmMapping>, LookupError> {
+    let matches = conversion_table.maps.iter()
+                                  .filter(|((_r_src, r_dest), _v)| std::mem::discriminant(resource) == std::mem::discriminant(r_dest))     // filter for any (should be only one) entry where the destination resource enum _variant_ matches that of the given resource
+                                  .map(|(_k, v)| v);                                                                                       // just grab the value
+
+    single_match(matches)
+}
+
+/// Same lookup, but keyed off the *source* resource -- used when converting forwards
+pub fn find_mappings_for_src_resource<'a>(resource: &Resource, conversion_table: &'a Almanac) -> Result<&'a Vec<FarmMapping>, LookupError> {
+    let matches = conversion_table.maps.iter()
+                                  .filter(|((r_src, _r_dest), _v)| std::mem::discriminant(resource) == std::mem::discriminant(r_src))
+                                  .map(|(_k, v)| v);
+
+    single_match(matches)
+}
+
+// Two maps for the same resource would make the choice between them depend on HashMap iteration order, so that's
+// an error rather than picking one
+fn single_match<'a>(mut matches: impl Iterator<Item = &'a Vec<FarmMapping>>) -> Result<&'a Vec<FarmMapping>, LookupError> {
+    match (matches.next(), matches.count()) {
+        (None, _)               => Err(LookupError::NotFound),
+        (Some(mappings), 0)     => Ok(mappings),
+        (Some(_), extra)        => Err(LookupError::Ambiguous(extra + 1))
+    }
+}
+
+pub fn create_conversion_table(path: &Path) -> Result<(Vec<Num>, Almanac), AlmanacError> {
+    create_conversion_table_with(path, &ParseOptions::default())
+}
+
+/// [`create_conversion_table`] with the given parser options
+pub fn create_conversion_table_with(path: &Path, options: &ParseOptions) -> Result<(Vec<Num>, Almanac), AlmanacError> {
+    parse_almanac_str_with(&read_input(path)?, options)
+}
+
+/// Parses an almanac split across several files (say, the seeds in one and the maps in another) as if they were one.
+/// Errors in one file, including failing to read it, come back as [`AlmanacError::InPart`] with its index in `paths`.
+pub fn create_conversion_table_from_paths<P: AsRef<Path>>(paths: &[P], options: &ParseOptions) -> Result<(Vec<Num>, Almanac), AlmanacError> {
+    let inputs = paths.iter()
+                      .enumerate()
+                      .map(|(part, path)| read_input(path.as_ref()).map_err(|error| AlmanacError::InPart { part, error: Box::new(error) }))
+                      .collect::<Result<Vec<String>, AlmanacError>>()?;
+
+    parse_almanac_parts(&inputs, options)
+}
+
+// Reads a whole input file. Compressed inputs get decoded up front, everything else is read as plain text.
+fn read_input(path: &Path) -> Result<String, AlmanacError> {
+    #[cfg(feature = "gzip")]
+    if path.extension().is_some_and(|ext| ext == "gz") {
+        use std::io::Read;
+
+        let mut input = String::new();
+        let file = fs::File::open(path).map_err(AlmanacError::FileOpen)?;
+        flate2::read::GzDecoder::new(file).read_to_string(&mut input).map_err(AlmanacError::FileOpen)?;
+
+        return Ok(input);
+    }
+
+    fs::read_to_string(path).map_err(AlmanacError::FileOpen)
+}
+
+/// Parses the seeds line and every map from any line-based source (a file, stdin, an in-memory Cursor...)
+pub fn parse_almanac<R: BufRead>(reader: R) -> Result<(Vec<Num>, Almanac), AlmanacError> {
+    parse_almanac_with(reader, &ParseOptions::default())
+}
+
+/// [`parse_almanac`] with the given parser options. A line that can't be read (including one that isn't UTF-8) is a
+/// [`AlmanacError::FileOpen`], the same as for a file given by path, rather than the end of the input.
+pub fn parse_almanac_with<R: BufRead>(reader: R, options: &ParseOptions) -> Result<(Vec<Num>, Almanac), AlmanacError> {
+    let lines = reader.lines()
+                      .collect::<Result<Vec<String>, io::Error>>()
+                      .map_err(AlmanacError::FileOpen)?;
+
+    parse_lines(lines.iter(), options).map(|(seeds, almanac, _warnings)| (seeds, almanac))
+}
+
+/// Parses an almanac already held in memory, e.g. an inline string literal in a test
+pub fn parse_almanac_str(input: &str) -> Result<(Vec<Num>, Almanac), AlmanacError> {
+    parse_almanac_str_with(input, &ParseOptions::default())
+}
+
+/// [`parse_almanac_str`], but a seed that isn't a number is an error (reporting the offending token) instead of
+/// being dropped
+pub fn parse_almanac_str_strict(input: &str) -> Result<(Vec<Num>, Almanac), AlmanacError> {
+    parse_almanac_str_with(input, &ParseOptions { strict: true, ..ParseOptions::default() })
+}
+
+/// Parses several pieces of one almanac as if they were a single input. Each piece ends its last section, so a map
+/// can't run on into the next piece, and counts its own lines. An error in one piece comes back as
+/// [`AlmanacError::InPart`], naming the piece.
+pub fn parse_almanac_parts<S: AsRef<str>>(parts: &[S], options: &ParseOptions) -> Result<(Vec<Num>, Almanac), AlmanacError> {
+    let mut parser = SectionParser::new(options, parse_map_header);
+    for (part, input) in parts.iter().enumerate() {
+        parser.feed(input.as_ref().lines()).map_err(|error| AlmanacError::InPart { part, error: Box::new(error) })?;
+    }
+
+    let (seeds, maps, _warnings) = parser.finish()?;
+    Ok((seeds, Almanac::try_from(maps)?.with_negative_allowed(options.allow_negative)))
+}
+
+/// Parses an almanac held in memory, with the given options
+pub fn parse_almanac_str_with(input: &str, options: &ParseOptions) -> Result<(Vec<Num>, Almanac), AlmanacError> {
+    parse_lines(input.lines(), options).map(|(seeds, almanac, _warnings)| (seeds, almanac))
+}
+
+/// [`parse_almanac_str`] for slightly corrupt files: malformed mapping lines are skipped rather than failing the
+/// parse, and each one skipped is described in the returned warnings. Everything else is still an error.
+pub fn parse_almanac_str_lenient(input: &str) -> Result<(Vec<Num>, Almanac, Vec<String>), AlmanacError> {
+    parse_lines(input.lines(), &ParseOptions { skip_bad_lines: true, ..ParseOptions::default() })
+}
+
+/// Downloads an almanac (e.g. a raw gist) and parses it
+#[cfg(feature = "http")]
+pub fn parse_almanac_url(url: &str) -> Result<(Vec<Num>, Almanac), AlmanacError> {
+    let input = ureq::get(url).call()
+                              .map_err(|e| AlmanacError::Fetch(e.to_string()))?
+                              .into_string()
+                              .map_err(|e| AlmanacError::Fetch(e.to_string()))?;
+
+    parse_almanac_str(&input)
+}
+
+// Reads every number off a seeds line
+pub(crate) fn parse_seed_numbers(line: &str) -> Vec<Num> {
+    parse_seed_numbers_after(line, SEEDS_KEYWORD)
+}
+
+// Reads every number off a seeds line labelled with `keyword`
+fn parse_seed_numbers_after(line: &str, keyword: &str) -> Vec<Num> {
+    line.strip_prefix(keyword)                  // Toss the list header, even with no space after it
+        .unwrap_or(line)
+        .split_whitespace()
+        .flat_map(|s| s.parse::<Num>())         // &str -> Num, tossing any Err
+        .collect::<Vec<Num>>()                  // Collect as vec of Num
+}
+
+/// Reads a seeds line the part 2 way, as (start, length) pairs. Nothing gets expanded, so this stays small however
+/// many seeds the ranges cover.
+pub fn parse_seed_ranges(line: &str) -> Vec<(Num, Num)> {
+    seed_ranges(&parse_seed_numbers(line))
+}
+
+/// Pairs up already-parsed seed numbers into (start, length) ranges. A trailing unpaired number is ignored.
+pub fn seed_ranges(seeds: &[Num]) -> Vec<(Num, Num)> {
+    seeds.chunks_exact(2)
+         .map(|pair| (pair[0], pair[1]))
+         .collect()
+}
+
+/// How many seeds the part 2 ranges cover between them, which is how much work expanding them would be. Empty and
+/// negative-length ranges count for nothing, and the total can't overflow even when every range is `Num::MAX` long.
+pub fn total_seeds(ranges: &[(Num, Num)]) -> u128 {
+    ranges.iter()
+          .map(|(_start, len)| (*len).max(0) as u128)
+          .sum()
+}
+
+// Turns "<src>-to-<dest> map:" into its (src, dest) key. Anything else that looks like a header is a typo, not a
+// mapping line -- and so is a map that skips a step in the chain.
+fn parse_map_header(line: &str) -> Result<(Resource, Resource), AlmanacError> {
+    let src = parse_header_position(line, |name| name.parse::<Resource>().ok().map(|resource| resource_position(&resource)))?;
+
+    Ok((RESOURCE_ORDER[src](0), RESOURCE_ORDER[src + 1](0)))
+}
+
+// The header rules behind parse_map_header, for any chain of stages: `position` looks up where a stage name sits in
+// the chain, and the result is the position of the source stage
+pub(crate) fn parse_header_position(line: &str, position: impl Fn(&str) -> Option<usize>) -> Result<usize, AlmanacError> {
+    let unknown = || AlmanacError::UnknownMap(line.to_string());
+
+    let (src, dest) = line.trim_end_matches(" map:").split_once("-to-").ok_or_else(unknown)?;
+    let src = position(src).ok_or_else(unknown)?;
+
+    if position(dest) != Some(src + 1) {
+        return Err(unknown());
+    }
+
+    Ok(src)
+}
+
+// Reads a "dest src range" line. Anything other than exactly those three numbers is malformed, including a line
+// with three numbers among other tokens, and so is an inclusive end before its start.
+fn parse_mapping_line(line: &str, range_format: RangeFormat) -> Option<FarmMapping> {
+    let tokens = line.split_whitespace()
+                     .map(|s| s.parse::<Num>().ok())
+                     .collect::<Option<Vec<Num>>>()?;
+
+    match (&tokens[..], range_format) {
+        (&[dest_start, src_start, range], RangeFormat::Length)      => Some(FarmMapping { dest_start, src_start, range }),
+        (&[_, src_start, end], RangeFormat::End) if end < src_start => None,
+        (&[dest_start, src_start, end], RangeFormat::End)           => Some(FarmMapping { dest_start, src_start, range: end.saturating_sub(src_start).saturating_add(1) }),
+        _                                                           => None
+    }
+}
+
+// Parses the seeds line for parse_lines. Lenient parsing drops anything that isn't a number, strict parsing fails on
+// it, and either way a line with no seeds at all is an error. `line_number` counts from 1, for the error.
+fn read_seeds(line: &str, line_number: usize, options: &ParseOptions) -> Result<Vec<Num>, AlmanacError> {
+    if options.strict {
+        let bad_token = line.strip_prefix(options.seeds_keyword)
+                            .unwrap_or(line)
+                            .split_whitespace()
+                            .find(|token| token.parse::<Num>().is_err());
+
+        if let Some(token) = bad_token {
+            return Err(AlmanacError::BadSeeds { line: line_number, content: token.to_string() });
+        }
+    }
+
+    let seeds = parse_seed_numbers_after(line, options.seeds_keyword);
+    if seeds.is_empty() {
+        return Err(AlmanacError::BadSeeds { line: line_number, content: line.to_string() });
+    }
+
+    Ok(seeds)
+}
+
+// A labelled seeds line counts wherever it turns up. An empty keyword labels nothing.
+fn is_seeds_line(line: &str, options: &ParseOptions) -> bool {
+    !options.seeds_keyword.is_empty() && line.starts_with(options.seeds_keyword)
+}
+
+// The puzzle's own chain through the state machine, building the maps into an Almanac
+fn parse_lines<S: AsRef<str>>(lines: impl Iterator<Item = S>, options: &ParseOptions) -> Result<(Vec<Num>, Almanac, Vec<String>), AlmanacError> {
+    let mut parser = SectionParser::new(options, parse_map_header);
+    parser.feed(lines)?;
+
+    let (seeds, maps, warnings) = parser.finish()?;
+    Ok((seeds, Almanac::try_from(maps)?.with_negative_allowed(options.allow_negative), warnings))
+}
+
+// What SectionParser reads: the seeds, each map keyed however its header resolved, and any warnings
+pub(crate) type Sections<K> = (Vec<Num>, HashMap<K, Vec<FarmMapping>>, Vec<String>);
+
+// The parsing state machine behind every entry point. `parse_header` decides what each map header is keyed by, so the
+// same rules serve a ChainAlmanac's stages as well as an Almanac's resources.
+pub(crate) struct SectionParser<'o, K, F> {
+    options: &'o ParseOptions<'o>,
+    parse_header: F,
+    seeds: Vec<Num>,
+    maps: HashMap<K, Vec<FarmMapping>>,
+    warnings: Vec<String>
+}
+
+impl<'o, K: Copy + Eq + Hash, F: Fn(&str) -> Result<K, AlmanacError>> SectionParser<'o, K, F> {
+    pub(crate) fn new(options: &'o ParseOptions<'o>, parse_header: F) -> Self {
+        SectionParser { options, parse_header, seeds: Vec::new(), maps: HashMap::new(), warnings: Vec::new() }
+    }
+
+    // Parses one whole input, a line at a time. Each input is its own file as far as line numbers and byte-order marks
+    // go, and ends whatever section it's in, so a map can't run on into the next one.
+    pub(crate) fn feed<S: AsRef<str>>(&mut self, lines: impl Iterator<Item = S>) -> Result<(), AlmanacError> {
+        let options = self.options;
+        let mut current_resource: Option<K> = None;
+
+        for (line_index, line) in lines.enumerate() {
+            // Trailing whitespace (including the \r left over from CRLF endings) would stop the headers matching. This
+            // also turns whitespace-only lines into empty ones, so they separate sections like a truly blank line.
+            let line = line.as_ref().trim_end();
+
+            // Some editors start UTF-8 files with a byte-order mark, which would otherwise stick to the seeds label
+            let line = if line_index == 0 { line.strip_prefix('\u{FEFF}').unwrap_or(line) } else { line };
+
+            match line {
+                // Comments are for annotating fixtures and never mean anything
+                _ if line.trim_start().starts_with('#') => {}
+
+                // Handle state transitions. The key goes in straight away so a section with no mapping lines still
+                // counts as a map, one that converts everything unchanged. A second section for the same map is an
+                // error, as it would be from AlmanacBuilder or a saved almanac.
+                _ if line.ends_with(" map:") => {
+                    let key = (self.parse_header)(line)?;
+                    match self.maps.entry(key) {
+                        Entry::Occupied(_)  => return Err(AlmanacError::DuplicateMap(line.trim_end_matches(" map:").to_string())),
+                        Entry::Vacant(slot) => { slot.insert(Vec::new()); }
+                    }
+                    current_resource = Some(key);
+                }
+
+                // A labelled seeds line counts wherever it turns up, even after the maps
+                _ if is_seeds_line(line, options) => self.seeds = read_seeds(line, line_index + 1, options)?,
+
+                // Handle general lines
+                _ => {
+                    match current_resource {
+                        None => {
+                            // We must be on the very first line, or the first empty line. If non-empty, parse it as a list of seed numbers.
+                            if !line.is_empty() {
+                                self.seeds = read_seeds(line, line_index + 1, options)?;
+                            }
+                        }
+                        Some(resource_tuple) => {
+                            // This is a mapping line, or an empty line.
+                            if !line.is_empty() {
+                                match parse_mapping_line(line, options.range_format) {
+                                    Some(mapping) => self.maps.entry(resource_tuple).or_default().push(mapping),
+                                    None => {
+                                        let error = AlmanacError::BadMapping { line: line_index + 1, content: line.to_string() };
+                                        if !options.skip_bad_lines {
+                                            return Err(error);
+                                        }
+
+                                        self.warnings.push(error.to_string());
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    pub(crate) fn finish(self) -> Result<Sections<K>, AlmanacError> {
+        // Without seeds there's nothing to solve
+        if self.seeds.is_empty() {
+            return Err(AlmanacError::NoSeeds);
+        }
+
+        Ok((self.seeds, self.maps, self.warnings))
+    }
+}
+
+/// Checks that no two mappings within a map claim the same source numbers (which would make conversion depend on
+/// the order they're listed in), returning the first overlapping pair found
+pub fn validate_almanac(almanac: &Almanac) -> Result<(), (FarmMapping, FarmMapping)> {
+    for mappings in almanac.maps.values() {
+        let mut by_src = mappings.clone();
+        by_src.sort_by_key(|mapping| mapping.src_start);
+
+        // Once sorted, any overlap has to show up between neighbours
+        for pair in by_src.windows(2) {
+            if pair[0].overlaps_src(&pair[1]) {
+                return Err((pair[0], pair[1]));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// The seeds that pass through every map without hitting a single range. On a real puzzle input that usually
+/// means something went wrong while parsing.
+pub fn seeds_hitting_no_mapping(seeds: &[Num], almanac: &Almanac) -> Vec<Num> {
+    seeds.iter()
+         .copied()
+         .filter(|seed| {
+             let mut resource = Resource::Seed(*seed);
+
+             while !matches!(resource, Resource::Location(_)) {
+                 let (next, mapping) = convert_forward_explained(resource, almanac);
+                 if mapping.is_some() {
+                     return false;
+                 }
+
+                 resource = next;
+             }
+
+             true
+         })
+         .collect()
+}
+
+/// Renders the almanac as a Graphviz DOT graph: one node per resource and one edge per map, following
+/// Seed -> Location and labelled with how many ranges the map has. Pipe it into `dot -Tpng` to view.
+pub fn to_dot(almanac: &Almanac) -> String {
+    let node = |resource: &Resource| {
+        let mut name = resource_name(resource).to_string();
+        name[..1].make_ascii_uppercase();
+        name
+    };
+
+    let maps = almanac.maps_in_chain_order();
+
+    let mut dot = String::from("digraph almanac {\n    rankdir=LR;\n");
+
+    for make_resource in RESOURCE_ORDER {
+        dot.push_str(&format!("    \"{}\";\n", node(&make_resource(0))));
+    }
+    for ((src, dest), mappings) in maps {
+        dot.push_str(&format!("    \"{}\" -> \"{}\" [label=\"{} ranges\"];\n", node(src), node(dest), mappings.len()));
+    }
+
+    dot.push_str("}\n");
+    dot
+}
+
+/// Writes seeds and maps back out as almanac text, maps in Seed -> Location order, which parses back to the same
+/// almanac. Handy for normalizing hand-edited inputs.
+pub fn write_almanac<W: io::Write>(w: &mut W, seeds: &[Num], almanac: &Almanac) -> io::Result<()> {
+    write_almanac_with(w, seeds, almanac, &ParseOptions::default())
+}
+
+/// [`write_almanac`] in the format `options` reads: its seeds keyword and range format, so the text parses back
+/// through [`parse_almanac_str_with`] with the same options. An inclusive end can't describe an empty mapping, so
+/// with [`RangeFormat::End`] one is an `InvalidInput` error.
+pub fn write_almanac_with<W: io::Write>(w: &mut W, seeds: &[Num], almanac: &Almanac, options: &ParseOptions) -> io::Result<()> {
+    let seeds = seeds.iter().map(|seed| seed.to_string()).collect::<Vec<String>>();
+    writeln!(w, "{} {}", options.seeds_keyword, seeds.join(" "))?;
+
+    let maps = almanac.maps_in_chain_order();
+
+    for ((src, dest), mappings) in maps {
+        writeln!(w)?;
+        writeln!(w, "{}-to-{} map:", resource_name(src), resource_name(dest))?;
+
+        for mapping in mappings {
+            match options.range_format {
+                RangeFormat::Length                     => writeln!(w, "{}", mapping)?,
+                RangeFormat::End if mapping.range <= 0  => return Err(io::Error::new(io::ErrorKind::InvalidInput, format!("Mapping {} has no inclusive end", mapping))),
+                RangeFormat::End                        => writeln!(w, "{} {} {}", mapping.dest_start, mapping.src_start, mapping.src_start.saturating_add(mapping.range).saturating_sub(1))?
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Converts resource *backwards* through the conversion table -- so Locations get converted to Humidity, Humidity to Temperature, etc.
+///
+/// Fails with the unconverted resource when the table doesn't have exactly one map leading to it, or when the result
+/// would be negative -- resource numbers never are, so that means a malformed mapping. Almanacs parsed with
+/// [`ParseOptions::allow_negative`] skip that second check.
+pub fn convert_resource(resource: Resource, conversion_table: &Almanac) -> Result<Resource, Resource> {
+    find_mappings(&resource, conversion_table, Direction::Backward).map_err(|_| resource)?;
+
+    convert(resource, conversion_table, Direction::Backward)
+}
+
+// Stepping back onto a negative number means a malformed mapping, unless negatives are allowed. Fails with the
+// resource the step started from.
+fn reject_negative(resource: Resource, converted: Resource, allow_negative: bool) -> Result<Resource, Resource> {
+    if !allow_negative && *get_resource_num(&converted) < 0 {
+        return Err(resource);
+    }
+
+    Ok(converted)
+}
+
+/// Which way [`convert`] moves along the chain
+#[derive(Clone)]
+#[derive(Copy)]
+#[derive(PartialEq)]
+#[derive(Eq)]
+#[derive(Debug)]
+pub enum Direction {
+    Forward,    // Towards Location
+    Backward    // Towards Seed
+}
+
+/// Converts one step in either direction, through the map leaving the resource (forwards) or the map leading to it
+/// (backwards). A missing map passes the number through unchanged either way. Going backwards, a negative result
+/// fails with the unconverted resource, as in [`convert_resource`]; going forwards never fails. Panics when there's
+/// no next step: forwards from a Location or backwards from a Seed.
+pub fn convert(resource: Resource, almanac: &Almanac, direction: Direction) -> Result<Resource, Resource> {
+    let (converted, _mapping) = convert_through(resource, find_mappings(&resource, almanac, direction).ok(), direction);
+
+    match direction {
+        Direction::Forward  => Ok(converted),
+        Direction::Backward => reject_negative(resource, converted, almanac.allow_negative)
+    }
+}
+
+/// Converts `n` through just the `from`-to-`to` map, e.g. checking a single step against a hand calculation. Only
+/// the variants of `from` and `to` matter. Without such a map the number comes back unchanged.
+pub fn convert_one(n: Num, from: Resource, to: Resource, almanac: &Almanac) -> Num {
+    mapping_at(n, from, to, almanac).map_or(n, |mapping| mapping.apply_forward(n))
+}
+
+/// How far the `from`-to-`to` map shifts `n` (`dest - src` of the mapping it falls in), or 0 where it passes
+/// through unchanged. Looks up the map the same way as [`convert_one`].
+pub fn offset_at(n: Num, from: Resource, to: Resource, almanac: &Almanac) -> Num {
+    mapping_at(n, from, to, almanac).map_or(0, |mapping| mapping.dest_start.saturating_sub(mapping.src_start))
+}
+
+// The mapping in the `from`-to-`to` map whose source range holds `n`, if there's such a map and such a mapping
+fn mapping_at(n: Num, from: Resource, to: Resource, almanac: &Almanac) -> Option<&FarmMapping> {
+    almanac.maps.iter()
+                .find(|((r_src, r_dest), _mappings)| discriminant(&from) == discriminant(r_src) && discriminant(&to) == discriminant(r_dest))
+                .and_then(|(_key, mappings)| mappings.iter().find(|mapping| mapping.contains_src(n)))
+}
+
+/// Converts resource *forwards* through the conversion table -- so Seeds get converted to Soil, Soil to Fertilizer, etc.
+pub fn convert_resource_forward(resource: Resource, conversion_table: &Almanac) -> Resource {
+    convert_forward_explained(resource, conversion_table).0
+}
+
+/// [`convert_resource_forward`], also reporting which mapping fired: its index in the map's (dest-sorted) list, or
+/// `None` when the number passed through unchanged
+pub fn convert_forward_explained(resource: Resource, conversion_table: &Almanac) -> (Resource, Option<usize>) {
+    convert_through(resource, find_mappings(&resource, conversion_table, Direction::Forward).ok(), Direction::Forward)
+}
+
+// The map a step in `direction` goes through: the one leaving the resource forwards, the one leading to it backwards
+fn find_mappings<'a>(resource: &Resource, conversion_table: &'a Almanac, direction: Direction) -> Result<&'a Vec<FarmMapping>, LookupError> {
+    match direction {
+        Direction::Forward  => find_mappings_for_src_resource(resource, conversion_table),
+        Direction::Backward => find_mappings_for_dest_resource(resource, conversion_table)
+    }
+}
+
+// The one conversion step every direction-specific function is built on. Finds the mapping holding the number on
+// the side it's coming from, translates it to the other side and moves the resource one stage along, reporting
+// which mapping (by index) fired. A missing map carries the number through unchanged.
+fn convert_through(resource: Resource, mappings: Option<&Vec<FarmMapping>>, direction: Direction) -> (Resource, Option<usize>) {
+    let resource_num = *get_resource_num(&resource);
+
+    let found = mappings.and_then(|mappings| {
+        let index = match direction {
+            Direction::Forward  => mappings.iter().position(|mapping| mapping.contains_src(resource_num)),
+            // Mappings are sorted by dest_start with non-overlapping ranges, so at most one can contain the number.
+            // contains_dest decides the endpoints, so this agrees with the linear scan forwards.
+            Direction::Backward => mappings.binary_search_by(|mapping| {
+                                       if mapping.contains_dest(resource_num) {
+                                           Ordering::Equal
+                                       }
+                                       else if mapping.dest_start > resource_num {
+                                           Ordering::Greater
+                                       }
+                                       else {
+                                           Ordering::Less
+                                       }
+                                   }).ok()
+        }?;
+
+        Some((index, &mappings[index]))
+    });
+
+    let index = found.map(|(index, _mapping)| index);
+    let converted = found.map(|(_index, mapping)| match direction {
+        Direction::Forward  => mapping.apply_forward(resource_num),
+        Direction::Backward => mapping.apply_backward(resource_num)
+    });
+
+    // With no mapping applied, converted is None and the number carries over as is
+    match direction {
+        Direction::Forward  => (to_next_resource(resource, converted), index),
+        Direction::Backward => (to_previous_resource(resource, converted), index)
+    }
+}
+
+/// An [`Almanac`] with its maps indexed by resource variant up front, so each conversion step is a single hash
+/// lookup instead of a scan over every map. Worth building once before a brute-force run.
+pub struct PreparedAlmanac<'a> {
+    by_src: HashMap<Discriminant<Resource>, &'a Vec<FarmMapping>>,
+    by_dest: HashMap<Discriminant<Resource>, &'a Vec<FarmMapping>>,
+    allow_negative: bool
+}
+
+impl<'a> PreparedAlmanac<'a> {
+    pub fn new(almanac: &'a Almanac) -> PreparedAlmanac<'a> {
+        PreparedAlmanac {
+            by_src: almanac.maps.iter().map(|((src, _dest), mappings)| (discriminant(src), mappings)).collect(),
+            by_dest: almanac.maps.iter().map(|((_src, dest), mappings)| (discriminant(dest), mappings)).collect(),
+            allow_negative: almanac.allow_negative
+        }
+    }
+
+    /// Same as [`convert_resource_forward`]
+    pub fn convert_forward(&self, resource: Resource) -> Resource {
+        convert_through(resource, self.by_src.get(&discriminant(&resource)).copied(), Direction::Forward).0
+    }
+
+    /// Same as [`convert_resource`]
+    pub fn convert_backward(&self, resource: Resource) -> Result<Resource, Resource> {
+        match self.by_dest.get(&discriminant(&resource)) {
+            Some(mappings)  => reject_negative(resource, convert_through(resource, Some(mappings), Direction::Backward).0, self.allow_negative),
+            None            => Err(resource)
+        }
+    }
+
+    /// Same as [`location_for_seed`]
+    pub fn location_for_seed(&self, seed: Num) -> Num {
+        let mut resource = Resource::Seed(seed);
+
+        while !matches!(resource, Resource::Location(_)) {
+            resource = self.convert_forward(resource);
+        }
+
+        *get_resource_num(&resource)
+    }
+}
+
+impl<'a> From<&'a Almanac> for PreparedAlmanac<'a> {
+    fn from(almanac: &'a Almanac) -> PreparedAlmanac<'a> {
+        PreparedAlmanac::new(almanac)
+    }
+}
+
+/// Walks a single seed forward through every map to its Location number
+pub fn location_for_seed(seed: Num, almanac: &Almanac) -> Num {
+    walk_to(seed, almanac, Resource::Location)
+}
+
+// Walks a seed forward until it reaches the `target` stage, returning its number there
+fn walk_to(seed: Num, almanac: &Almanac, target: fn(Num) -> Resource) -> Num {
+    let target = discriminant(&target(0));
+    let mut resource = Resource::Seed(seed);
+
+    while discriminant(&resource) != target {
+        resource = convert_resource_forward(resource, almanac);
+    }
+
+    *get_resource_num(&resource)
+}
+
+/// Every resource a seed passes through on its way to a Location, starting with the seed itself
+pub fn conversion_path(seed: Num, almanac: &Almanac) -> Vec<Resource> {
+    let mut path = vec![Resource::Seed(seed)];
+
+    while !matches!(path.last(), Some(Resource::Location(_))) {
+        path.push(convert_resource_forward(*path.last().unwrap(), almanac));
+    }
+
+    path
+}
+
+/// The number a seed has at every stage on its way to a Location, from [`convert_all_stages`]
+#[derive(Clone)]
+#[derive(Copy)]
+#[derive(PartialEq)]
+#[derive(Eq)]
+#[derive(Debug)]
+pub struct SeedJourney {
+    pub seed: Num,
+    pub soil: Num,
+    pub fertilizer: Num,
+    pub water: Num,
+    pub light: Num,
+    pub temperature: Num,
+    pub humidity: Num,
+    pub location: Num
+}
+
+/// [`conversion_path`] with each stage in its own named field
+pub fn convert_all_stages(seed: Num, almanac: &Almanac) -> SeedJourney {
+    let path = conversion_path(seed, almanac);
+    let stage = |position: usize| *get_resource_num(&path[position]);
+
+    SeedJourney {
+        seed: stage(0),
+        soil: stage(1),
+        fertilizer: stage(2),
+        water: stage(3),
+        light: stage(4),
+        temperature: stage(5),
+        humidity: stage(6),
+        location: stage(7)
+    }
+}
+
+/// Back-converts a Location all the way to the Seed that lands on it. A missing map passes the number through
+/// unchanged. `None` when some step back would go negative (see [`Almanac::convert_backward`]), i.e. no seed
+/// reaches this location.
+pub fn seed_for_location(location: Num, almanac: &Almanac) -> Option<Num> {
+    let mut resource = Resource::Location(location);
+
+    // Stops once there's nothing before the current resource, i.e. at the Seed
+    while try_to_previous_resource(resource, None).is_some() {
+        resource = almanac.convert_backward(resource).ok()?;
+    }
+
+    Some(*get_resource_num(&resource))
+}
+
+/// The Location of each seed, in the same order as `seeds` and keeping duplicates, unlike [`solve_all`]
+pub fn convert_many(seeds: &[Num], almanac: &Almanac) -> Vec<Num> {
+    seeds.iter()
+         .map(|seed| location_for_seed(*seed, almanac))
+         .collect()
+}
+
+/// Maps each seed to the Location it ends up at
+pub fn solve_all(seeds: &[Num], almanac: &Almanac) -> HashMap<Num, Num> {
+    seeds.iter()
+         .map(|seed| (*seed, location_for_seed(*seed, almanac)))
+         .collect()
+}
+
+/// Every seed's Location, lowest first, so the part 1 answer is the first element
+pub fn sorted_locations(seeds: &[Num], almanac: &Almanac) -> Vec<Num> {
+    let mut locations = seeds.iter()
+                             .map(|seed| location_for_seed(*seed, almanac))
+                             .collect::<Vec<Num>>();
+    locations.sort_unstable();
+    locations
+}
+
+/// Counts how many seeds land in each `bucket_size`-wide block of locations, keyed by the block's first location.
+/// Panics if `bucket_size` isn't positive.
+pub fn location_histogram(seeds: &[Num], almanac: &Almanac, bucket_size: Num) -> BTreeMap<Num, usize> {
+    assert!(bucket_size > 0, "bucket size has to be positive");
+
+    let mut histogram = BTreeMap::new();
+    for seed in seeds {
+        let location = location_for_seed(*seed, almanac);
+        *histogram.entry(location.div_euclid(bucket_size) * bucket_size).or_default() += 1;
+    }
+
+    histogram
+}
+
+/// [`lowest_location`] for any stage: converts each seed only as far as `target` (e.g. `Resource::Humidity`) and
+/// returns the lowest number reached there
+pub fn lowest_resource(seeds: &[Num], almanac: &Almanac, target: fn(Num) -> Resource) -> Num {
+    seeds.iter()
+         .map(|seed| walk_to(*seed, almanac, target))
+         .min()
+         .unwrap_or(Num::MAX)
+}
+
+/// Runs each seed forward through every map and returns the lowest Location reached
+pub fn lowest_location(seeds: &[Num], almanac: &Almanac) -> Num {
+    seeds.iter()
+         .map(|seed| location_for_seed(*seed, almanac))
+         .min()
+         .unwrap_or(Num::MAX)
+}
+
+/// [`lowest_location`] along with the seed that got there, as (seed, location). The first seed wins a tie, and with
+/// no seeds there's no answer at all.
+pub fn lowest_location_with_seed(seeds: &[Num], almanac: &Almanac) -> Option<(Num, Num)> {
+    seeds.iter()
+         .map(|seed| (*seed, location_for_seed(*seed, almanac)))
+         .min_by_key(|(_seed, location)| *location)
+}
+
+/// [`lowest_location`], calling `on_improve(seed, location)` every time a seed beats the lowest Location so far, so
+/// long runs can report progress as they go
+pub fn lowest_location_streaming(seeds: &[Num], almanac: &Almanac, mut on_improve: impl FnMut(Num, Num)) -> Num {
+    let mut lowest = Num::MAX;
+
+    for seed in seeds {
+        let location = location_for_seed(*seed, almanac);
+
+        if location < lowest {
+            lowest = location;
+            on_improve(*seed, location);
+        }
+    }
+
+    lowest
+}
+
+/// Brute-force counterpart to [`lowest_location_ranges`]: expands every (start, length) range and converts each
+/// seed individually, spread across rayon's thread pool. Only useful as a correctness check for the interval solver.
+///
+/// Every seed is independent, so the work splits evenly -- on an 8-core machine expect roughly a 6-7x speedup
+/// over a single-threaded loop (just short of linear, since the per-seed conversion is cheap enough that
+/// scheduling and the final reduction start to show).
+#[cfg(feature = "rayon")]
+pub fn lowest_location_ranges_bruteforce(seed_ranges: &[(Num, Num)], almanac: &Almanac) -> Num {
+    lowest_location_ranges_bruteforce_with_progress(seed_ranges, almanac, u64::MAX, |_processed| ())
+}
+
+/// [`lowest_location_ranges_bruteforce`] that calls `progress` with the running seed count every `interval` seeds,
+/// so a long run can report how far along it is. The callback always runs on the calling thread.
+#[cfg(feature = "rayon")]
+pub fn lowest_location_ranges_bruteforce_with_progress(seed_ranges: &[(Num, Num)], almanac: &Almanac, interval: u64, mut progress: impl FnMut(u64)) -> Num {
+    use rayon::prelude::*;
+
+    assert!(interval > 0, "Progress interval must be at least one seed");
+
+    let prepared = PreparedAlmanac::new(almanac);
+    let mut lowest = Num::MAX;
+    let mut processed: u64 = 0;
+
+    for (start, len) in seed_ranges {
+        let end = start.saturating_add(*len);
+        let mut chunk_start = *start;
+
+        // Work through the range in parallel chunks that stop at each multiple of the interval
+        while chunk_start < end {
+            let until_report = interval - processed % interval;
+            let step = (until_report as u128).min(end.saturating_sub(chunk_start) as u128);
+            let chunk_end = chunk_start + step as Num;
+
+            let chunk_lowest = (chunk_start..chunk_end).into_par_iter()
+                                                       .map(|seed| prepared.location_for_seed(seed))
+                                                       .reduce(|| Num::MAX, Num::min);
+            lowest = lowest.min(chunk_lowest);
+
+            processed += step as u64;
+            if processed.is_multiple_of(interval) {
+                progress(processed);
+            }
+
+            chunk_start = chunk_end;
+        }
+    }
+
+    lowest
+}
+
+/// Propagates (start, length) seed intervals forward through every map and returns the lowest Location reached
+pub fn lowest_location_ranges(seed_ranges: &[(Num, Num)], almanac: &Almanac) -> Num {
+    let mut intervals: Vec<(Num, Num)> = seed_ranges.iter()
+                                                    .filter(|(_start, len)| *len > 0)
+                                                    .copied()
+                                                    .collect();
+    let mut stage = Resource::Seed(0);
+
+    while !matches!(stage, Resource::Location(_)) {
+        if let Ok(mappings) = find_mappings_for_src_resource(&stage, almanac) {
+            intervals = intervals.into_iter()
+                                 .flat_map(|interval| map_coverage(interval, mappings))
+                                 .map(|(interval, _mapping)| interval)
+                                 .collect();
+        }
+
+        stage = to_next_resource(stage, None);
+    }
+
+    intervals.iter()
+             .map(|(start, _len)| *start)
+             .min()
+             .unwrap_or(Num::MAX)
+}
+
+/// Part 2 the other way round: tries locations 0, 1, 2, ... and back-converts each until one lands in a seed range.
+/// Fast when the answer is small, but its running time grows with the answer rather than with the input. Only
+/// trustworthy when every map is one-to-one, as they are in puzzle inputs -- otherwise a location can have more
+/// than one seed and the back-conversion only finds one of them. The scan starts at 0, or for almanacs that allow
+/// negatives at the lowest number any seed or mapping could produce, since nothing lower is reachable. It gives up
+/// with `Num::MAX` past the highest such number.
+pub fn lowest_location_by_scan(seed_ranges: &[(Num, Num)], almanac: &Almanac) -> Num {
+    let in_seed_ranges = |seed: Num| seed_ranges.iter().any(|range| intersect(*range, (seed, 1)).is_some());
+
+    if seed_ranges.iter().all(|(_start, len)| *len <= 0) {
+        return Num::MAX;
+    }
+
+    // Every number is either a seed carried through unchanged or the output of some mapping, so the (start, length)
+    // intervals of both bound every reachable location
+    let reachable = seed_ranges.iter()
+                               .copied()
+                               .chain(almanac.maps.values().flatten().map(|mapping| (mapping.dest_start, mapping.range)))
+                               .filter(|(_start, len)| *len > 0)
+                               .collect::<Vec<(Num, Num)>>();
+    let lowest_reachable = reachable.iter().map(|(start, _len)| *start).min().unwrap_or(0);
+    let past_highest = reachable.iter().map(|(start, len)| start.saturating_add(*len)).max().unwrap_or(0);
+    let first = if almanac.allows_negative() { lowest_reachable } else { lowest_reachable.max(0) };
+
+    (first..past_highest).find(|location| seed_for_location(*location, almanac).is_some_and(in_seed_ranges))
+                         .unwrap_or(Num::MAX)
+}
+
+/// Part 1 without ever holding the maps in memory, for almanacs too big to load. A first pass over `reader` finds the
+/// seeds and where each map's lines start; then each map in turn is streamed from the file, converting every seed at
+/// once. Only the seeds and one line of input are kept, at the cost of reading the file twice. Parsing follows the
+/// same rules as [`parse_almanac`].
+pub fn solve_streaming<R: BufRead + Seek>(reader: R) -> Result<Num, AlmanacError> {
+    solve_streaming_with(reader, &ParseOptions::default())
+}
+
+/// [`solve_streaming`] with the given parser options. `allow_negative` makes no difference, since only forward
+/// conversion is involved.
+pub fn solve_streaming_with<R: BufRead + Seek>(mut reader: R, options: &ParseOptions) -> Result<Num, AlmanacError> {
+    let mut seeds: Vec<Num> = Vec::new();
+    let mut sections: Vec<Option<(u64, usize)>> = vec![None; RESOURCE_ORDER.len() - 1];    // (offset, line number) after each map's header
+    let mut line = String::new();
+    let mut line_index = 0;
+    let mut in_map = false;
+
+    while read_next_line(&mut reader, &mut line)? {
+        line_index += 1;
+        let trimmed = line.trim_end();
+        // A byte-order mark only ever starts the file, as in SectionParser::feed
+        let trimmed = if line_index == 1 { trimmed.strip_prefix('\u{FEFF}').unwrap_or(trimmed) } else { trimmed };
+
+        if trimmed.trim_start().starts_with('#') {
+            continue;
+        }
+
+        if trimmed.ends_with(" map:") {
+            let (src, _dest) = parse_map_header(trimmed)?;
+            let section = &mut sections[resource_position(&src)];
+            if section.is_some() {
+                return Err(AlmanacError::DuplicateMap(trimmed.trim_end_matches(" map:").to_string()));
+            }
+            *section = Some((reader.stream_position().map_err(AlmanacError::FileOpen)?, line_index));
+            in_map = true;
+        }
+        else if is_seeds_line(trimmed, options) || (!in_map && !trimmed.is_empty()) {
+            seeds = read_seeds(trimmed, line_index, options)?;
+        }
+    }
+
+    if seeds.is_empty() {
+        return Err(AlmanacError::NoSeeds);
+    }
+
+    let mut nums = seeds;
+    for start in &sections {
+        let mut converted: Vec<Option<Num>> = vec![None; nums.len()];
+
+        if let Some((offset, header_line)) = start {
+            reader.seek(SeekFrom::Start(*offset)).map_err(AlmanacError::FileOpen)?;
+            line_index = *header_line;
+
+            while read_next_line(&mut reader, &mut line)? {
+                line_index += 1;
+                let trimmed = line.trim_end();
+
+                if trimmed.ends_with(" map:") {
+                    break;
+                }
+                if trimmed.is_empty() || trimmed.trim_start().starts_with('#') || is_seeds_line(trimmed, options) {
+                    continue;
+                }
+
+                let mapping = match parse_mapping_line(trimmed, options.range_format) {
+                    Some(mapping)                       => mapping,
+                    None if options.skip_bad_lines      => continue,
+                    None                                => return Err(AlmanacError::BadMapping { line: line_index, content: trimmed.to_string() })
+                };
+                for (num, slot) in nums.iter().zip(converted.iter_mut()) {
+                    if slot.is_none() && mapping.contains_src(*num) {
+                        *slot = Some(mapping.apply_forward(*num));
+                    }
+                }
+            }
+        }
+
+        // Seeds no mapping claimed carry through unchanged
+        nums = nums.iter()
+                   .zip(converted)
+                   .map(|(num, converted)| converted.unwrap_or(*num))
+                   .collect();
+    }
+
+    Ok(nums.into_iter().min().unwrap_or(Num::MAX))
+}
+
+// Reads the next line into `line`, giving false at the end of the input
+fn read_next_line<R: BufRead>(reader: &mut R, line: &mut String) -> Result<bool, AlmanacError> {
+    line.clear();
+    Ok(reader.read_line(line).map_err(AlmanacError::FileOpen)? > 0)
+}
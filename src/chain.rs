@@ -0,0 +1,134 @@
+// Almanacs over a configurable chain of stages, for variant puzzles that don't follow seed -> location
+
+use crate::{almanac::{parse_header_position, SectionParser}, find_mappings_for_src_resource, Almanac, AlmanacError, FarmMapping, Num, ParseOptions, RESOURCE_NAMES, RESOURCE_ORDER};
+
+/// The stage names a [`ChainAlmanac`] converts through, in order. The default is the puzzle's own seed -> location
+/// chain.
+#[derive(Clone)]
+#[derive(PartialEq)]
+#[derive(Eq)]
+#[derive(Debug)]
+pub struct Chain {
+    stages: Vec<String>
+}
+
+impl Chain {
+    pub fn new(stages: Vec<String>) -> Self {
+        Chain { stages }
+    }
+
+    /// Number of stages, one more than the number of maps
+    pub fn len(&self) -> usize {
+        self.stages.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.stages.is_empty()
+    }
+
+    /// Looks up a stage index by its name in map headers
+    pub fn stage(&self, name: &str) -> Option<usize> {
+        self.stages.iter().position(|stage| stage == name)
+    }
+
+    pub fn name(&self, stage: usize) -> &str {
+        &self.stages[stage]
+    }
+}
+
+impl Default for Chain {
+    fn default() -> Self {
+        Chain::new(RESOURCE_NAMES.iter().map(|name| name.to_string()).collect())
+    }
+}
+
+/// A number at some stage of a [`Chain`]
+#[derive(Clone)]
+#[derive(Copy)]
+#[derive(Hash)]
+#[derive(PartialEq)]
+#[derive(Eq)]
+#[derive(Debug)]
+pub struct Resource {
+    pub stage: usize,
+    pub num: Num
+}
+
+/// An almanac keyed by stage index instead of resource variant: `maps[i]` converts stage `i` to stage `i + 1`, sorted
+/// by `dest_start` like an [`Almanac`]'s. A map the input never mentions stays empty, which passes numbers through
+/// unchanged.
+#[derive(Clone)]
+#[derive(PartialEq)]
+#[derive(Eq)]
+#[derive(Debug)]
+pub struct ChainAlmanac {
+    chain: Chain,
+    maps: Vec<Vec<FarmMapping>>
+}
+
+impl ChainAlmanac {
+    /// Parses an almanac whose map headers name stages from `chain`, returning the seeds line alongside it
+    pub fn parse_str(input: &str, chain: Chain) -> Result<(Vec<Num>, ChainAlmanac), AlmanacError> {
+        ChainAlmanac::parse_str_with(input, chain, &ParseOptions::default())
+    }
+
+    /// [`ChainAlmanac::parse_str`] with the given parser options. Apart from the stage names in the headers, the
+    /// input follows the same rules as [`crate::parse_almanac_str_with`].
+    pub fn parse_str_with(input: &str, chain: Chain, options: &ParseOptions) -> Result<(Vec<Num>, ChainAlmanac), AlmanacError> {
+        let mut parser = SectionParser::new(options, |line: &str| parse_header_position(line, |name| chain.stage(name)));
+        parser.feed(input.lines())?;
+
+        let (seeds, sections, _warnings) = parser.finish()?;
+
+        let mut maps = vec![Vec::new(); chain.len().saturating_sub(1)];
+        for (map, mut mappings) in sections {
+            // Same ordering as an Almanac, so the two compare equal after conversion
+            mappings.sort_by_key(|mapping| mapping.dest_start);
+            maps[map] = mappings;
+        }
+
+        Ok((seeds, ChainAlmanac { chain, maps }))
+    }
+
+    pub fn chain(&self) -> &Chain {
+        &self.chain
+    }
+
+    /// Moves a resource on to the next stage. Panics on the last stage, which has nowhere to go.
+    pub fn convert_forward(&self, resource: Resource) -> Resource {
+        let num = self.maps[resource.stage].iter()
+                                           .find(|mapping| mapping.contains_src(resource.num))
+                                           .map_or(resource.num, |mapping| mapping.apply_forward(resource.num));
+
+        Resource { stage: resource.stage + 1, num }
+    }
+
+    /// Walks a number from the first stage all the way to the last
+    pub fn convert_to_last(&self, num: Num) -> Num {
+        let mut resource = Resource { stage: 0, num };
+
+        while resource.stage + 1 < self.chain.len() {
+            resource = self.convert_forward(resource);
+        }
+
+        resource.num
+    }
+
+    /// The lowest last-stage number any of `seeds` reaches
+    pub fn lowest(&self, seeds: &[Num]) -> Num {
+        seeds.iter()
+             .map(|seed| self.convert_to_last(*seed))
+             .min()
+             .unwrap_or(Num::MAX)
+    }
+}
+
+impl From<&Almanac> for ChainAlmanac {
+    fn from(almanac: &Almanac) -> Self {
+        let maps = RESOURCE_ORDER[..RESOURCE_ORDER.len() - 1].iter()
+                                                             .map(|stage| find_mappings_for_src_resource(&stage(0), almanac).cloned().unwrap_or_default())
+                                                             .collect();
+
+        ChainAlmanac { chain: Chain::default(), maps }
+    }
+}
@@ -0,0 +1,188 @@
+// Command-line handling for the day5 binary. Lives in the library so the argument parsing can be tested.
+
+use std::{str::FromStr, time::{Duration, Instant}};
+
+use crate::{resource_name, Almanac, BruteForceSolver, FarmMapping, IntervalSolver, LocationScanSolver, Num, RangeFormat, Solver};
+#[cfg(feature = "serde")]
+use crate::{lowest_location, seed_ranges};
+
+/// Which puzzle answers to print
+#[derive(Clone)]
+#[derive(Copy)]
+#[derive(Debug)]
+#[derive(Default)]
+#[derive(PartialEq)]
+pub enum Part {
+    #[default]
+    Both,
+    One,
+    Two
+}
+
+impl Part {
+    pub fn includes_one(&self) -> bool {
+        matches!(self, Part::Both | Part::One)
+    }
+
+    pub fn includes_two(&self) -> bool {
+        matches!(self, Part::Both | Part::Two)
+    }
+}
+
+/// Which [`Solver`] answers part 2, picked with `--strategy`
+#[derive(Clone)]
+#[derive(Copy)]
+#[derive(Debug)]
+#[derive(Default)]
+#[derive(PartialEq)]
+pub enum Strategy {
+    #[default]
+    Interval,
+    BruteForce,
+    Scan
+}
+
+impl Strategy {
+    pub fn solver(&self) -> Box<dyn Solver> {
+        match self {
+            Strategy::Interval      => Box::new(IntervalSolver),
+            Strategy::BruteForce    => Box::new(BruteForceSolver),
+            Strategy::Scan          => Box::new(LocationScanSolver)
+        }
+    }
+}
+
+impl FromStr for Strategy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "interval"      => Ok(Strategy::Interval),
+            "brute-force"   => Ok(Strategy::BruteForce),
+            "scan"          => Ok(Strategy::Scan),
+            _               => Err(format!("Unknown strategy {:?}", s))
+        }
+    }
+}
+
+#[derive(Debug)]
+#[derive(Default)]
+#[derive(PartialEq)]
+pub struct Options {
+    pub paths: Vec<String>,         // Input files, parsed as one; none means stdin (or ./day5.txt when nothing is piped in)
+    pub trace: Option<Num>,         // Seed whose full conversion path should be printed
+    pub dot: bool,                  // Print the almanac as a Graphviz graph instead of solving
+    pub seeds: Vec<Num>,            // Seeds given with --seed, converted instead of the file's seeds line
+    pub part: Part,                 // Set by --part 1 / --part 2; both by default
+    pub bench_compare: bool,        // Cross-check part 2 against the brute-force solver, timing both
+    pub histogram: Option<Num>,     // Bucket size for a histogram of the seeds' locations
+    pub json: bool,                 // Print the answers as JSON instead of the usual report
+    pub inspect: bool,              // Describe the input and exit without solving
+    pub range_format: RangeFormat,  // Set by --range-format end for "dest src end" mapping lines
+    pub time: bool,                 // Report how long parsing and each part took
+    pub validate: bool,             // Run every structural check on the input instead of solving
+    pub strategy: Strategy          // How to solve part 2
+}
+
+/// Parses the arguments following the program name
+pub fn parse_args<I: IntoIterator<Item = String>>(args: I) -> Result<Options, String> {
+    let mut options = Options::default();
+    let mut args = args.into_iter();
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--trace" => options.trace = Some(parse_flag_value(&arg, args.next())?),
+            "--dot"   => options.dot = true,
+            "--json"  => options.json = true,
+            "--inspect" => options.inspect = true,
+            "--time"  => options.time = true,
+            "--validate" => options.validate = true,
+            "--range-format" => options.range_format = parse_flag_value(&arg, args.next())?,
+            "--strategy" => options.strategy = parse_flag_value(&arg, args.next())?,
+            "--bench-compare" => options.bench_compare = true,
+            "--seed"  => options.seeds.push(parse_flag_value(&arg, args.next())?),
+            "--histogram" => {
+                match parse_flag_value::<Num>(&arg, args.next())? {
+                    size if size > 0 => options.histogram = Some(size),
+                    size => return Err(format!("Histogram buckets have to be at least 1 wide, not {}", size))
+                }
+            }
+            "--part"  => {
+                options.part = match parse_flag_value::<u8>(&arg, args.next())? {
+                    1 => Part::One,
+                    2 => Part::Two,
+                    n => return Err(format!("There is no part {}", n))
+                }
+            }
+            _ if arg.starts_with("--") => return Err(format!("Unknown flag {}", arg)),
+            _ => options.paths.push(arg)
+        }
+    }
+
+    Ok(options)
+}
+
+/// What `--inspect` prints: how much was parsed, then the lowest and highest source and destination numbers each
+/// map covers, in chain order
+pub fn inspect_summary(seeds: &[Num], almanac: &Almanac) -> String {
+    let stats = almanac.stats();
+    let mut summary = format!("{} seeds, {} maps, {} ranges\n", seeds.len(), stats.maps, stats.total_ranges);
+
+    let maps = almanac.maps_in_chain_order();
+
+    for ((src, dest), mappings) in maps {
+        // Inclusive bounds, so an empty map has none
+        let bounds = |start: fn(&FarmMapping) -> Num| {
+            let min = mappings.iter().map(start).min()?;
+            let max = mappings.iter().map(|mapping| start(mapping).saturating_add(mapping.range.saturating_sub(1))).max()?;
+            Some((min, max))
+        };
+
+        let header = format!("{}-to-{}", resource_name(src), resource_name(dest));
+        match (bounds(|mapping| mapping.src_start), bounds(|mapping| mapping.dest_start)) {
+            (Some((src_min, src_max)), Some((dest_min, dest_max))) => {
+                summary.push_str(&format!("{}: src {}..={}, dest {}..={}\n", header, src_min, src_max, dest_min, dest_max));
+            }
+            _ => summary.push_str(&format!("{}: empty\n", header))
+        }
+    }
+
+    summary
+}
+
+/// Runs `f`, returning its result along with the wall-clock time it took
+pub fn timed<T>(f: impl FnOnce() -> T) -> (T, Duration) {
+    let start = Instant::now();
+    let result = f();
+
+    (result, start.elapsed())
+}
+
+fn parse_flag_value<T: FromStr>(flag: &str, value: Option<String>) -> Result<T, String> {
+    let value = value.ok_or_else(|| format!("{} needs a value", flag))?;
+
+    value.parse::<T>().map_err(|_| format!("Invalid value for {}: {}", flag, value))
+}
+
+/// The answers as `--json` prints them. Parts left out with `--part` are left out here too.
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize)]
+pub struct Answers {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub part1: Option<Num>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub part2: Option<Num>,
+    pub seeds: Vec<Num>
+}
+
+/// Solves the requested parts, part 2 with `solver`, and renders them as a JSON object
+#[cfg(feature = "serde")]
+pub fn answers_json(seeds: &[Num], almanac: &Almanac, part: Part, solver: &dyn Solver) -> String {
+    let answers = Answers {
+        part1: part.includes_one().then(|| lowest_location(seeds, almanac)),
+        part2: part.includes_two().then(|| solver.solve(&seed_ranges(seeds), almanac)),
+        seeds: seeds.to_vec()
+    };
+
+    serde_json::to_string(&answers).unwrap()     // Plain numbers always serialize
+}
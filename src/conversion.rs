@@ -0,0 +1,294 @@
+// The pure conversion core: mappings, resources and the interval arithmetic. Only needs `core` and `alloc`, so it
+// still builds when the `std` feature is off.
+
+use alloc::{format, string::String, vec, vec::Vec};
+use core::{fmt, mem::discriminant, str::FromStr};
+
+#[derive(Clone)]
+#[derive(Copy)]
+#[derive(PartialEq)]
+#[derive(Eq)]
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FarmMapping {
+    pub dest_start: Num,
+    pub src_start: Num,
+    pub range: Num
+}
+
+/// Ranges are half-open everywhere: `src_start` (or `dest_start`) is the first number covered and `start + range` the
+/// first one past the end. Whether a single number is covered is decided by [`FarmMapping::contains_src`] and
+/// [`FarmMapping::contains_dest`], which every conversion uses; whether two ranges share any numbers is decided by
+/// [`intersect`], which the interval solver and the overlap checks use.
+impl FarmMapping {
+    /// Whether `n` falls in the source range, `src_start..src_start + range`
+    pub fn contains_src(&self, n: Num) -> bool {
+        self.src_start <= n && n < self.src_start.saturating_add(self.range)
+    }
+
+    /// Translates a source number to its destination. Only meaningful when [`FarmMapping::contains_src`] holds.
+    pub fn apply_forward(&self, n: Num) -> Num {
+        self.dest_start.saturating_add(n.saturating_sub(self.src_start))
+    }
+
+    /// Whether `n` falls in the destination range, `dest_start..dest_start + range`
+    pub fn contains_dest(&self, n: Num) -> bool {
+        self.dest_start <= n && n < self.dest_start.saturating_add(self.range)
+    }
+
+    /// Translates a destination number back to its source. Only meaningful when [`FarmMapping::contains_dest`] holds.
+    pub fn apply_backward(&self, n: Num) -> Num {
+        self.src_start.saturating_add(n.saturating_sub(self.dest_start))
+    }
+
+    /// Whether the two source ranges have any number in common. Empty ranges have nothing in common with anything.
+    pub fn overlaps_src(&self, other: &FarmMapping) -> bool {
+        intersect((self.src_start, self.range), (other.src_start, other.range)).is_some()
+    }
+}
+
+/// Prints the mapping as it appears in the input, "dest src range"
+impl fmt::Display for FarmMapping {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {} {}", self.dest_start, self.src_start, self.range)
+    }
+}
+
+/// The integer type behind every resource number and range. The `i128` feature widens it for inputs whose ranges
+/// run up against the limits of `i64`.
+#[cfg(not(feature = "i128"))]
+pub type Num = i64;
+#[cfg(feature = "i128")]
+pub type Num = i128;
+
+/// Resources order by stage first (Seed lowest, Location highest) and then by number, so sorting a mix of variants
+/// groups them in chain order. The variants have to stay declared in chain order for that to hold.
+#[derive(Clone)]
+#[derive(Copy)]
+#[derive(Hash)]
+#[derive(PartialEq)]
+#[derive(Eq)]
+#[derive(PartialOrd)]
+#[derive(Ord)]
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Resource {
+    Seed(Num),
+    Soil(Num),
+    Fertilizer(Num),
+    Water(Num),
+    Light(Num),
+    Temperature(Num),
+    Humidity(Num),
+    Location(Num)
+}
+
+pub fn get_resource_num(resource: &Resource) -> &Num {
+    match resource {
+        Resource::Seed(x) |
+        Resource::Soil(x) |
+        Resource::Fertilizer(x) |
+        Resource::Water(x) |
+        Resource::Light(x) |
+        Resource::Temperature(x) |
+        Resource::Humidity(x) |
+        Resource::Location(x) => x
+    }
+}
+
+pub fn get_resource_num_mut(resource: &mut Resource) -> &mut Num {
+    match resource {
+        Resource::Seed(x) |
+        Resource::Soil(x) |
+        Resource::Fertilizer(x) |
+        Resource::Water(x) |
+        Resource::Light(x) |
+        Resource::Temperature(x) |
+        Resource::Humidity(x) |
+        Resource::Location(x) => x
+    }
+}
+
+/// The same variant as `resource`, carrying `n` instead
+pub fn with_num(resource: Resource, n: Num) -> Resource {
+    RESOURCE_ORDER[resource_position(&resource)](n)
+}
+
+/// Every resource constructor in chain order, Seed first and Location last. This is the one place the ordering lives.
+pub const RESOURCE_ORDER: [fn(Num) -> Resource; 8] = [
+    Resource::Seed,
+    Resource::Soil,
+    Resource::Fertilizer,
+    Resource::Water,
+    Resource::Light,
+    Resource::Temperature,
+    Resource::Humidity,
+    Resource::Location
+];
+
+/// Builds the zero-numbered resource named as in map headers, e.g. "soil" -> Soil(0)
+impl FromStr for Resource {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Resource, String> {
+        RESOURCE_NAMES.iter()
+                      .position(|known| *known == name)
+                      .map(|position| RESOURCE_ORDER[position](0))
+                      .ok_or_else(|| format!("Unknown resource {:?}", name))
+    }
+}
+
+/// Prints the resource the way a person would say it, e.g. "soil 81"
+impl fmt::Display for Resource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", resource_name(self), get_resource_num(self))
+    }
+}
+
+/// Lowercase names of each resource as they appear in map headers, in the same order as [`RESOURCE_ORDER`]
+pub const RESOURCE_NAMES: [&str; 8] = ["seed", "soil", "fertilizer", "water", "light", "temperature", "humidity", "location"];
+
+/// Index of the resource's variant in [`RESOURCE_ORDER`]
+pub fn resource_position(resource: &Resource) -> usize {
+    RESOURCE_ORDER.iter()
+                  .position(|make_resource| discriminant(&make_resource(0)) == discriminant(resource))
+                  .unwrap()     // Every variant is in the table
+}
+
+/// The resource's name as used in map headers, e.g. "soil"
+pub fn resource_name(resource: &Resource) -> &'static str {
+    RESOURCE_NAMES[resource_position(resource)]
+}
+
+pub fn to_previous_resource(resource: Resource, new_num: Option<Num>) -> Resource {
+    try_to_previous_resource(resource, new_num).expect("Cannot back-convert from a Seed")
+}
+
+/// [`to_previous_resource`] without the panic: a Seed has no previous resource, so it gives `None`
+pub fn try_to_previous_resource(resource: Resource, new_num: Option<Num>) -> Option<Resource> {
+    match resource_position(&resource) {
+        0           => None,
+        position    => Some(RESOURCE_ORDER[position - 1](new_num.unwrap_or(*get_resource_num(&resource))))
+    }
+}
+
+pub fn to_next_resource(resource: Resource, new_num: Option<Num>) -> Resource {
+    match resource_position(&resource) {
+        position if position == RESOURCE_ORDER.len() - 1    => panic!("Cannot forward-convert from a Location"),
+        position                                            => RESOURCE_ORDER[position + 1](new_num.unwrap_or(*get_resource_num(&resource)))
+    }
+}
+
+/// The part two (start, length) intervals have in common, or `None` if they don't overlap. Intervals that only touch
+/// (one ending where the other starts) share nothing. Ends past the limits of `Num` are clamped rather than
+/// overflowing.
+pub fn intersect(a: (Num, Num), b: (Num, Num)) -> Option<(Num, Num)> {
+    let start = a.0.max(b.0);
+    let end = a.0.saturating_add(a.1).min(b.0.saturating_add(b.1));
+
+    (start < end).then(|| (start, end.saturating_sub(start)))
+}
+
+/// The (start, length) stretches of source numbers between 0 and the end of the highest mapping that no mapping
+/// covers -- the numbers the map passes through unchanged. Overlapping mappings are fine. With mappings below 0,
+/// the gaps start from the lowest of them instead.
+pub fn source_gaps(mappings: &[FarmMapping]) -> Vec<(Num, Num)> {
+    let mut by_src = mappings.iter()
+                             .filter(|mapping| mapping.range > 0)
+                             .copied()
+                             .collect::<Vec<FarmMapping>>();
+    by_src.sort_by_key(|mapping| mapping.src_start);
+
+    let mut gaps = Vec::new();
+    let mut covered_to: Num = by_src.first().map_or(0, |mapping| mapping.src_start.min(0));    // Everything below this is covered or already reported
+
+    for mapping in &by_src {
+        if mapping.src_start > covered_to {
+            gaps.push((covered_to, mapping.src_start - covered_to));
+        }
+
+        covered_to = covered_to.max(mapping.src_start.saturating_add(mapping.range));
+    }
+
+    gaps
+}
+
+/// Splits a single (start, length) interval against a map, translating the pieces covered by a mapping and
+/// carrying everything else through unchanged. Each output piece is tagged with the index of the mapping that
+/// translated it, or `None` where it fell through as identity.
+pub fn map_coverage(interval: (Num, Num), mappings: &[FarmMapping]) -> Vec<((Num, Num), Option<usize>)> {
+    let mut converted: Vec<((Num, Num), Option<usize>)> = Vec::new();
+    let mut unmapped: Vec<(Num, Num)> = vec![interval];
+
+    // All the arithmetic saturates: huge or adversarial ranges get clamped at the ends of Num instead of overflowing
+    for (index, FarmMapping{dest_start, src_start, range}) in mappings.iter().enumerate() {
+        let mut remaining: Vec<(Num, Num)> = Vec::new();
+
+        for (start, len) in unmapped {
+            let end = start.saturating_add(len);
+
+            if let Some((overlap_start, overlap_len)) = intersect((start, len), (*src_start, *range)) {
+                let overlap_end = overlap_start.saturating_add(overlap_len);
+
+                // The overlapping piece gets translated, anything either side of it is left for the other mappings
+                let translated = (dest_start.saturating_add(overlap_start.saturating_sub(*src_start)), overlap_len);
+                converted.push((translated, Some(index)));
+
+                if start < overlap_start {
+                    remaining.push((start, overlap_start.saturating_sub(start)));
+                }
+                if overlap_end < end {
+                    remaining.push((overlap_end, end.saturating_sub(overlap_end)));
+                }
+            }
+            else {
+                remaining.push((start, len));
+            }
+        }
+
+        unmapped = remaining;
+    }
+
+    // No mapping applied to these -- use default
+    converted.extend(unmapped.into_iter().map(|interval| (interval, None)));
+    converted
+}
+
+/// Every seed in a list of part two (start, length) ranges, one at a time, without expanding them into a `Vec`.
+/// Empty (or negative-length) ranges yield nothing, and a range running past `Num::MAX` stops there instead of
+/// overflowing.
+#[derive(Clone)]
+#[derive(Debug)]
+pub struct SeedRangeIter<'a> {
+    ranges: core::slice::Iter<'a, (Num, Num)>,
+    next: Num,
+    remaining: Num      // Seeds left in the current range, including `next`
+}
+
+impl<'a> SeedRangeIter<'a> {
+    pub fn new(ranges: &'a [(Num, Num)]) -> SeedRangeIter<'a> {
+        SeedRangeIter { ranges: ranges.iter(), next: 0, remaining: 0 }
+    }
+}
+
+impl Iterator for SeedRangeIter<'_> {
+    type Item = Num;
+
+    fn next(&mut self) -> Option<Num> {
+        while self.remaining <= 0 {
+            let (start, len) = self.ranges.next()?;
+            (self.next, self.remaining) = (*start, *len);
+        }
+
+        let seed = self.next;
+        match seed.checked_add(1) {
+            Some(next) => {
+                self.next = next;
+                self.remaining -= 1;
+            }
+            None => self.remaining = 0      // Nothing comes after Num::MAX
+        }
+
+        Some(seed)
+    }
+}
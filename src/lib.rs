@@ -0,0 +1,35 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+#[cfg(feature = "std")]
+mod almanac;
+#[cfg(feature = "std")]
+pub mod chain;
+#[cfg(feature = "std")]
+pub mod cli;
+mod conversion;
+#[cfg(feature = "random")]
+mod random;
+#[cfg(feature = "std")]
+mod solver;
+#[cfg(feature = "serde")]
+mod storage;
+
+// Without std only the conversion core is available
+pub use conversion::*;
+
+#[cfg(feature = "std")]
+pub use almanac::*;
+
+#[cfg(feature = "std")]
+pub use solver::{BruteForceSolver, IntervalSolver, LocationScanSolver, Solver};
+
+#[cfg(feature = "random")]
+pub use random::random_almanac;
+
+#[cfg(feature = "serde")]
+pub use storage::{load_almanac, save_almanac};
+
+#[cfg(feature = "bincode")]
+pub use storage::{load_almanac_bin, save_almanac_bin};
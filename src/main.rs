@@ -1,156 +1,169 @@
-use std::{collections::HashMap, fs::File, io::{BufReader, BufRead}, thread::current, mem::discriminant};
+use std::{env, io::{self, IsTerminal}, process::ExitCode};
 
-#[derive(Debug)]
-struct FarmMapping {
-    dest_start: i64,
-    src_start: i64,
-    range: i64
-}
+use day5::{cli, convert_forward_explained, create_conversion_table_from_paths, location_for_seed, location_histogram, lowest_location_ranges, parse_almanac_with, resource_name, seed_ranges, to_dot, total_seeds, Almanac, AlmanacError, BruteForceSolver, FarmMapping, Num, ParseOptions, Resource, Solver};
 
-type Almanac = HashMap<(Resource, Resource), Vec<FarmMapping>>;
-
-#[derive(Clone)]
-#[derive(Copy)]
-#[derive(Hash)]
-#[derive(PartialEq)]
-#[derive(Eq)]
-#[derive(Debug)]
-enum Resource {
-    Seed(i64),
-    Soil(i64),
-    Fertilizer(i64),
-    Water(i64),
-    Light(i64),
-    Temperature(i64),
-    Humidity(i64),
-    Location(i64)
-}
+// Prints every map in Seed -> Location order, laid out like the input file but with the columns lined up
+fn print_almanac(almanac: &Almanac) {
+    let maps = almanac.maps_in_chain_order();
 
-// The vec reference should live as long as the almanac does
-fn find_mappings_for_dest_resource<'a>(resource: &Resource, conversion_table: &'a Almanac) -> Option<&'a Vec<FarmMapping>> {
-    conversion_table.iter()
-                    .filter(|((_r_src, r_dest), v)| std::mem::discriminant(resource) == std::mem::discriminant(r_dest))     // filter for any (should be only one) entry where the destination resource enum _variant_ matches that of the given resource
-                    .map(|(k, v)| v)                                                                                        // just grab the value
-                    .next()                                                                                                 // return the option of the "next" (read: only or None) value    
-}
+    for ((src, dest), mappings) in maps {
+        let width = |column: fn(&FarmMapping) -> Num| mappings.iter().map(|m| column(m).to_string().len()).max().unwrap_or(0);
+        let (dest_width, src_width, range_width) = (width(|m| m.dest_start), width(|m| m.src_start), width(|m| m.range));
 
-fn get_resource_num(resource: &Resource) -> &i64 {
-    match resource {
-        Resource::Seed(x) |
-        Resource::Soil(x) |
-        Resource::Fertilizer(x) |
-        Resource::Water(x) |
-        Resource::Light(x) |
-        Resource::Temperature(x) |
-        Resource::Humidity(x) |
-        Resource::Location(x) => x
+        println!();
+        println!("{}-to-{} map:", resource_name(src), resource_name(dest));
+
+        for FarmMapping{dest_start, src_start, range} in mappings {
+            println!("{:>dest_width$} {:>src_width$} {:>range_width$}", dest_start, src_start, range);
+        }
     }
 }
 
-fn to_previous_resource(resource: Resource, new_num: Option<i64>) -> Resource {
-    match resource {
-        Resource::Seed(x)           => panic!("Cannot back-convert from a Seed"),
-        Resource::Soil(x)           => Resource::Seed(new_num.unwrap_or(x)),
-        Resource::Fertilizer(x)     => Resource::Soil(new_num.unwrap_or(x)),
-        Resource::Water(x)          => Resource::Fertilizer(new_num.unwrap_or(x)),
-        Resource::Light(x)          => Resource::Water(new_num.unwrap_or(x)),
-        Resource::Temperature(x)    => Resource::Light(new_num.unwrap_or(x)),
-        Resource::Humidity(x)       => Resource::Temperature(new_num.unwrap_or(x)),
-        Resource::Location(x)       => Resource::Humidity(new_num.unwrap_or(x))
+// Above this many seeds, expanding every range would take far too long to be a useful check
+const BENCH_COMPARE_LIMIT: u128 = 10_000_000;
+
+// Runs the interval solver and the brute-force one on the same ranges, timing both. Gives false, after saying so,
+// when they disagree.
+fn bench_compare(ranges: &[(Num, Num)], almanac: &Almanac) -> bool {
+    let (intervals, time) = cli::timed(|| lowest_location_ranges(ranges, almanac));
+    println!("Intervals:   {} in {:?}", intervals, time);
+
+    let total = total_seeds(ranges);
+    if total > BENCH_COMPARE_LIMIT {
+        println!("Brute force: skipped, {} seeds is more than {}", total, BENCH_COMPARE_LIMIT);
+        return true;
     }
-}
 
-fn create_conversion_table() -> Result<(Vec<i64>, Almanac), &'static str> {
-    if let Ok(file) = File::open("./day5.txt") {
-        let reader = BufReader::new(file);
-        let mut almanac: Almanac = HashMap::new();
-        let mut seeds: Vec<i64> = Vec::new();
-
-        // Start parsing the file
-        let mut current_resource: Option<(Resource, Resource)> = None;
-
-        for line in reader.lines().flatten() {
-            match line.as_str() {
-                // Handle state transitions
-                "seed-to-soil map:"             => current_resource = Some((Resource::Seed(0), Resource::Soil(0))),
-                "soil-to-fertilizer map:"       => current_resource = Some((Resource::Soil(0), Resource::Fertilizer(0))),
-                "fertilizer-to-water map:"      => current_resource = Some((Resource::Fertilizer(0), Resource::Water(0))),
-                "water-to-light map:"           => current_resource = Some((Resource::Water(0), Resource::Light(0))),
-                "light-to-temperature map:"     => current_resource = Some((Resource::Water(0), Resource::Temperature(0))),
-                "temperature-to-humidity map:"  => current_resource = Some((Resource::Temperature(0), Resource::Humidity(0))),
-                "humidity-to-location map:"     => current_resource = Some((Resource::Humidity(0), Resource::Location(0))),
-                
-                // Handle general lines
-                _ => {
-                    match current_resource {
-                        None => {
-                            // We must be on the very first line, or the first empty line. If non-empty, parse it as a list of seed numbers.
-                            if !line.is_empty() {
-                                seeds = line.split(" ")
-                                            .filter(|s| *s != "seeds:")     // Toss the list header
-                                            .map(|s| s.parse::<i64>())      // &str -> i64
-                                            .flatten()                      // Toss any Err
-                                            .collect::<Vec<i64>>();         // Collect as vec of i64
-                            }
-                        }
-                        Some(resource_tuple) => {
-                            // This is a mapping line, or an empty line.
-                            if !line.is_empty() {
-                               let mut tokens = line.split(" ")
-                                                    .map(|s| s.parse::<i64>())
-                                                    .flatten()
-                                                    .collect::<Vec<i64>>();
-
-                                if !almanac.contains_key(&resource_tuple) {
-                                    almanac.insert(resource_tuple.clone(), Vec::new());
-                                }
-
-                                almanac.get_mut(&resource_tuple).unwrap().push(FarmMapping {
-                                    dest_start: tokens.remove(0),
-                                    src_start: tokens.remove(0),
-                                    range: tokens.remove(0)
-                                });
-                            }
-                        }
-                    }
-                }
-            }
-        }
-        
-        return Ok((seeds, almanac));
+    // Parallel with the rayon feature, serial without
+    let (expanded, time) = cli::timed(|| BruteForceSolver.solve(ranges, almanac));
+    println!("Brute force: {} in {:?}", expanded, time);
+
+    if intervals != expanded {
+        eprintln!("The interval and brute-force solvers disagree: {} vs {}", intervals, expanded);
+        return false;
     }
 
-    // ...
-    Err("Unable to open file ./day5.txt")
+    true
 }
 
-// Converts resource *backwards* through the conversion table -- so Locations get converted to Humidity, Humidity to Temperature, etc.
-fn convert_resource(resource: Resource, conversion_table: &Almanac) -> Resource {
-    if let Some(mappings) = find_mappings_for_dest_resource(&resource, conversion_table) {
-        // We have a mappings vec. See if any of the ranges apply.
-        let resource_num = get_resource_num(&resource);
+// Exits with 2 for a bad command line, 1 when the input can't be parsed and 3 when --bench-compare finds the
+// solvers disagreeing
+fn main() -> ExitCode {
+    let options = match cli::parse_args(env::args().skip(1)) {
+        Ok(options) => options,
+        Err(e) => {
+            eprintln!("{}", e);
+            return ExitCode::from(2);
+        }
+    };
+
+    // Without an input path, read piped input from stdin, falling back to the puzzle input in the working
+    // directory when there's nothing piped in.
+    let stdin = io::stdin();
+    let paths = match options.paths {
+        paths if paths.is_empty() && stdin.is_terminal() => vec![String::from("./day5.txt")],
+        paths => paths
+    };
+
+    let parse_options = ParseOptions { range_format: options.range_format, ..ParseOptions::default() };
+    let (parsed, parse_time) = cli::timed(|| {
+        if paths.is_empty() {
+            parse_almanac_with(stdin.lock(), &parse_options)
+        }
+        else {
+            create_conversion_table_from_paths(&paths, &parse_options)
+        }
+    });
+
+    match parsed {
+        Ok((_seeds, almanac)) if options.dot => print!("{}", to_dot(&almanac)),
+        Ok((seeds, almanac)) if options.inspect => print!("{}", cli::inspect_summary(&seeds, &almanac)),
+        Ok((_seeds, almanac)) if options.validate => {
+            if let Err(issues) = almanac.validate_all() {
+                for issue in issues {
+                    println!("{}", issue);
+                }
+                return ExitCode::FAILURE;
+            }
 
-        for FarmMapping{dest_start, src_start, range} in mappings {
-            if dest_start <= resource_num && *resource_num < (dest_start + range) {
-                return to_previous_resource(resource, Some(src_start + (resource_num - dest_start)))
+            println!("No issues found");
+        }
+        #[cfg(feature = "serde")]
+        Ok((seeds, almanac)) if options.json => println!("{}", cli::answers_json(&seeds, &almanac, options.part, options.strategy.solver().as_ref())),
+        #[cfg(not(feature = "serde"))]
+        Ok(_) if options.json => {
+            eprintln!("--json needs the serde feature");
+            return ExitCode::from(2);
+        }
+        Ok((_seeds, almanac)) if !options.seeds.is_empty() => {
+            for seed in &options.seeds {
+                println!("Seed {}: location {}", seed, location_for_seed(*seed, &almanac));
             }
         }
+        Ok((seeds, almanac)) => {
+            let stats = almanac.stats();
+            println!("Parsed {} maps, {} ranges", stats.maps, stats.total_ranges);
+            if options.time {
+                println!("Parsing took {:?}", parse_time);
+            }
+            println!("Seeds: {:?}", seeds);
+            print_almanac(&almanac);
+            println!();
+
+            if let Some(seed) = options.trace {
+                let mut resource = Resource::Seed(seed);
+                println!("Trace: {}", resource);
+
+                while !matches!(resource, Resource::Location(_)) {
+                    let (next, mapping) = convert_forward_explained(resource, &almanac);
+                    match mapping {
+                        Some(index) => println!("    -> {} via mapping {}", next, index),
+                        None        => println!("    -> {} (unmapped)", next)
+                    }
 
-        // No mapping applied -- use default
-        return to_previous_resource(resource, None);
-    }
-    else {
-        println!("Unable to find mappings for destination resource with discriminant {:?}", std::mem::discriminant(&resource));
-    }
+                    resource = next;
+                }
+                println!();
+            }
 
-    Resource::Seed(0)
-}
+            if options.part.includes_one() {
+                let (answer, time) = cli::timed(|| almanac.lowest_location(&seeds));
+                println!("Part 1: {}", answer);
+                if options.time {
+                    println!("Part 1 took {:?}", time);
+                }
+            }
 
-fn main() {
-    println!("Hello, world!");
+            if options.part.includes_two() {
+                // Part 2 reads the seeds line as (start, length) pairs
+                let ranges = seed_ranges(&seeds);
+                let (answer, time) = cli::timed(|| options.strategy.solver().solve(&ranges, &almanac));
+                println!("Part 2: {} ({} seeds in range)", answer, total_seeds(&ranges));
+                if options.time {
+                    println!("Part 2 took {:?}", time);
+                }
+            }
+
+            if let Some(bucket_size) = options.histogram {
+                println!();
+                for (bucket, count) in location_histogram(&seeds, &almanac, bucket_size) {
+                    println!("{:>12}: {:>4} {}", bucket, count, "#".repeat(count));
+                }
+            }
 
-    if let Ok((seeds, almanac)) = create_conversion_table() {
-        println!("Seeds: {:?}", seeds);
-        println!("Almanac: {:?}", almanac);
+            if options.bench_compare && !bench_compare(&seed_ranges(&seeds), &almanac) {
+                return ExitCode::from(3);
+            }
+        }
+        Err(AlmanacError::InPart { part, error }) => {
+            eprintln!("{}: {}", paths[part], error);
+            return ExitCode::FAILURE;
+        }
+        Err(e) => {
+            eprintln!("{}: {}", if paths.is_empty() { String::from("stdin") } else { paths.join(", ") }, e);
+            return ExitCode::FAILURE;
+        }
     }
+
+    ExitCode::SUCCESS
 }
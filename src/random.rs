@@ -0,0 +1,30 @@
+// Generating well-formed almanacs for property tests and benchmarks
+
+use rand::{seq::SliceRandom, Rng};
+
+use crate::{Almanac, ConversionTable, FarmMapping, Num, RESOURCE_ORDER};
+
+/// A random almanac with all seven maps, each `num_ranges_per_map` equally sized slots whose destinations are a
+/// shuffle of their sources. No two ranges in a map overlap on either side, every number stays far from the
+/// integer limits, and each map is a bijection -- so conversion works the same in both directions. Pass a seeded
+/// RNG (e.g. `StdRng::seed_from_u64`) for repeatable output.
+pub fn random_almanac<R: Rng>(rng: &mut R, num_ranges_per_map: usize) -> Almanac {
+    let maps = RESOURCE_ORDER.windows(2)
+                             .map(|stages| {
+                                 let (width, gap, offset) = (rng.gen_range(1..=1000 as Num), rng.gen_range(0..=1000 as Num), rng.gen_range(0..=1000 as Num));
+                                 let starts = (0..num_ranges_per_map as Num).map(|slot| offset + slot * (width + gap)).collect::<Vec<Num>>();
+
+                                 let mut dests = starts.clone();
+                                 dests.shuffle(rng);
+
+                                 let mappings = starts.iter()
+                                                      .zip(dests)
+                                                      .map(|(src_start, dest_start)| FarmMapping { dest_start, src_start: *src_start, range: width })
+                                                      .collect();
+
+                                 ((stages[0](0), stages[1](0)), mappings)
+                             })
+                             .collect::<ConversionTable>();
+
+    Almanac::try_from(maps).expect("one map per step of the chain")
+}
@@ -0,0 +1,61 @@
+// The part 2 approaches behind one interface, so the binary and the benchmarks can swap between them
+
+use crate::{lowest_location_by_scan, lowest_location_ranges, Almanac, Num};
+
+/// A way of finding the lowest Location any seed in a set of part 2 (start, length) ranges reaches. Every
+/// implementation gives the same answer on puzzle inputs; they differ in what their running time grows with.
+pub trait Solver {
+    fn solve(&self, seed_ranges: &[(Num, Num)], almanac: &Almanac) -> Num;
+}
+
+/// Converts every seed one at a time -- in parallel with the `rayon` feature. Grows with the number of seeds, so
+/// only practical for small inputs.
+#[derive(Clone)]
+#[derive(Copy)]
+#[derive(Default)]
+#[derive(Debug)]
+pub struct BruteForceSolver;
+
+impl Solver for BruteForceSolver {
+    #[cfg(feature = "rayon")]
+    fn solve(&self, seed_ranges: &[(Num, Num)], almanac: &Almanac) -> Num {
+        crate::lowest_location_ranges_bruteforce(seed_ranges, almanac)
+    }
+
+    #[cfg(not(feature = "rayon"))]
+    fn solve(&self, seed_ranges: &[(Num, Num)], almanac: &Almanac) -> Num {
+        let prepared = crate::PreparedAlmanac::new(almanac);
+
+        crate::SeedRangeIter::new(seed_ranges).map(|seed| prepared.location_for_seed(seed))
+                                              .min()
+                                              .unwrap_or(Num::MAX)
+    }
+}
+
+/// Pushes whole ranges through each map (see [`lowest_location_ranges`]). Grows with the number of mappings, not
+/// seeds, which makes it the default.
+#[derive(Clone)]
+#[derive(Copy)]
+#[derive(Default)]
+#[derive(Debug)]
+pub struct IntervalSolver;
+
+impl Solver for IntervalSolver {
+    fn solve(&self, seed_ranges: &[(Num, Num)], almanac: &Almanac) -> Num {
+        lowest_location_ranges(seed_ranges, almanac)
+    }
+}
+
+/// Tries locations upwards from the lowest possible one until one back-converts into a seed range (see
+/// [`lowest_location_by_scan`]). Grows with the answer.
+#[derive(Clone)]
+#[derive(Copy)]
+#[derive(Default)]
+#[derive(Debug)]
+pub struct LocationScanSolver;
+
+impl Solver for LocationScanSolver {
+    fn solve(&self, seed_ranges: &[(Num, Num)], almanac: &Almanac) -> Num {
+        lowest_location_by_scan(seed_ranges, almanac)
+    }
+}
@@ -0,0 +1,75 @@
+// Caching parsed almanacs on disk as JSON, or in bincode's compact binary format
+
+use std::{collections::hash_map::Entry, fs::File, io::{self, BufReader, BufWriter}, path::Path};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{resource_name, Almanac, AlmanacError, ConversionTable, FarmMapping, Resource};
+
+// JSON object keys have to be strings, so the (src, dest) keyed map is stored as a list of these instead
+#[derive(Serialize)]
+#[derive(Deserialize)]
+struct MapRecord {
+    src: Resource,
+    dest: Resource,
+    mappings: Vec<FarmMapping>
+}
+
+// Everything saved: the maps, plus whether negative numbers are real in them
+#[derive(Serialize)]
+#[derive(Deserialize)]
+struct AlmanacRecord {
+    allow_negative: bool,
+    maps: Vec<MapRecord>
+}
+
+/// Writes the almanac to `path` as JSON, along with whether it allows negative numbers
+pub fn save_almanac(almanac: &Almanac, path: &Path) -> io::Result<()> {
+    serde_json::to_writer(BufWriter::new(File::create(path)?), &to_records(almanac))?;
+    Ok(())
+}
+
+/// Reads back an almanac written by [`save_almanac`]
+pub fn load_almanac(path: &Path) -> io::Result<Almanac> {
+    let records: AlmanacRecord = serde_json::from_reader(BufReader::new(File::open(path)?))?;
+
+    from_records(records)
+}
+
+/// Writes the almanac to `path` with bincode: the same records as [`save_almanac`], but without the field names and
+/// digit strings, so real puzzle inputs come out a good deal smaller and load back faster
+#[cfg(feature = "bincode")]
+pub fn save_almanac_bin(almanac: &Almanac, path: &Path) -> io::Result<()> {
+    bincode::serialize_into(BufWriter::new(File::create(path)?), &to_records(almanac)).map_err(io::Error::other)
+}
+
+/// Reads back an almanac written by [`save_almanac_bin`]
+#[cfg(feature = "bincode")]
+pub fn load_almanac_bin(path: &Path) -> io::Result<Almanac> {
+    let records: AlmanacRecord = bincode::deserialize_from(BufReader::new(File::open(path)?)).map_err(io::Error::other)?;
+
+    from_records(records)
+}
+
+fn to_records(almanac: &Almanac) -> AlmanacRecord {
+    let maps = almanac.maps().iter()
+                             .map(|((src, dest), mappings)| MapRecord { src: *src, dest: *dest, mappings: mappings.clone() })
+                             .collect();
+
+    AlmanacRecord { allow_negative: almanac.allows_negative(), maps }
+}
+
+// A file edited by hand can name the same map twice, which has to fail like it would in the text format
+fn from_records(records: AlmanacRecord) -> io::Result<Almanac> {
+    let invalid = |e: AlmanacError| io::Error::new(io::ErrorKind::InvalidData, e);
+
+    let mut maps = ConversionTable::new();
+    for MapRecord { src, dest, mappings } in records.maps {
+        match maps.entry((src, dest)) {
+            Entry::Occupied(_)  => return Err(invalid(AlmanacError::DuplicateMap(format!("{}-to-{}", resource_name(&src), resource_name(&dest))))),
+            Entry::Vacant(slot) => { slot.insert(mappings); }
+        }
+    }
+
+    Ok(Almanac::try_from(maps).map_err(invalid)?.with_negative_allowed(records.allow_negative))
+}
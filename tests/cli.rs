@@ -0,0 +1,133 @@
+use std::path::Path;
+
+use day5::{cli::{parse_args, Part, Strategy}, create_conversion_table, location_for_seed, Num};
+#[cfg(feature = "serde")]
+use day5::{Almanac, IntervalSolver, Solver};
+
+fn args(args: &[&str]) -> Vec<String> {
+    args.iter().map(|arg| arg.to_string()).collect()
+}
+
+#[test]
+fn repeated_seed_flags_are_all_converted() {
+    let options = parse_args(args(&["--seed", "79", "--seed", "14"])).unwrap();
+    let (_seeds, almanac) = create_conversion_table(Path::new("./tests/fixtures/example.txt")).unwrap();
+
+    assert_eq!(options.seeds, vec![79, 14]);
+    assert_eq!(options.seeds.iter().map(|seed| location_for_seed(*seed, &almanac)).collect::<Vec<Num>>(), vec![82, 43]);
+}
+
+#[test]
+fn part_flag_selects_the_part() {
+    assert_eq!(parse_args(args(&["input.txt"])).unwrap().part, Part::Both);
+    assert_eq!(parse_args(args(&["--part", "2", "input.txt"])).unwrap().part, Part::Two);
+    assert!(parse_args(args(&["--part", "3"])).is_err());
+}
+
+#[test]
+fn bench_compare_flag() {
+    assert!(!parse_args(args(&["input.txt"])).unwrap().bench_compare);
+    assert!(parse_args(args(&["--bench-compare", "input.txt"])).unwrap().bench_compare);
+}
+
+#[cfg(feature = "rayon")]
+#[test]
+fn both_part_two_strategies_agree_on_example() {
+    let (seeds, almanac) = create_conversion_table(Path::new("./tests/fixtures/example.txt")).unwrap();
+    let ranges = day5::seed_ranges(&seeds);
+
+    assert_eq!(day5::lowest_location_ranges(&ranges, &almanac), 46);
+    assert_eq!(day5::lowest_location_ranges_bruteforce(&ranges, &almanac), 46);
+}
+
+#[test]
+fn histogram_needs_a_positive_bucket_size() {
+    assert_eq!(parse_args(args(&["--histogram", "10"])).unwrap().histogram, Some(10));
+    assert!(parse_args(args(&["--histogram", "0"])).is_err());
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn json_answers_for_example() {
+    let (seeds, almanac) = create_conversion_table(Path::new("./tests/fixtures/example.txt")).unwrap();
+    let json: serde_json::Value = serde_json::from_str(&day5::cli::answers_json(&seeds, &almanac, Part::Both, &IntervalSolver)).unwrap();
+
+    assert_eq!(json, serde_json::json!({ "part1": 35, "part2": 46, "seeds": [79, 14, 55, 13] }));
+
+    let part_two: serde_json::Value = serde_json::from_str(&day5::cli::answers_json(&seeds, &almanac, Part::Two, &IntervalSolver)).unwrap();
+    assert!(part_two.get("part1").is_none());
+
+    // Part 2 comes from whichever solver was picked
+    struct Fixed;
+    impl Solver for Fixed {
+        fn solve(&self, _seed_ranges: &[(Num, Num)], _almanac: &Almanac) -> Num {
+            7
+        }
+    }
+    let fixed: serde_json::Value = serde_json::from_str(&day5::cli::answers_json(&seeds, &almanac, Part::Two, &Fixed)).unwrap();
+    assert_eq!(fixed["part2"], 7);
+    assert_eq!(day5::cli::answers_json(&seeds, &almanac, Part::Two, Strategy::Scan.solver().as_ref()), day5::cli::answers_json(&seeds, &almanac, Part::Two, &IntervalSolver));
+}
+
+#[test]
+fn inspect_summary_for_example() {
+    let (seeds, almanac) = create_conversion_table(Path::new("./tests/fixtures/example.txt")).unwrap();
+    let summary = day5::cli::inspect_summary(&seeds, &almanac);
+
+    assert!(summary.starts_with("4 seeds, 7 maps, 18 ranges\n"));
+    assert!(summary.contains("seed-to-soil: src 50..=99, dest 50..=99\n"));
+    assert_eq!(summary.lines().count(), 8);
+}
+
+#[test]
+fn range_format_flag() {
+    assert_eq!(parse_args(args(&["input.txt"])).unwrap().range_format, day5::RangeFormat::Length);
+    assert_eq!(parse_args(args(&["--range-format", "end"])).unwrap().range_format, day5::RangeFormat::End);
+    assert!(parse_args(args(&["--range-format", "inclusive"])).is_err());
+}
+
+#[test]
+fn every_positional_argument_is_an_input() {
+    assert_eq!(parse_args(args(&["seeds.txt", "--part", "1", "maps.txt"])).unwrap().paths, vec!["seeds.txt", "maps.txt"]);
+    assert!(parse_args(args(&["--bogus", "input.txt"])).is_err());
+}
+
+#[test]
+fn binary_fails_on_a_missing_file() {
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_day5")).arg("./tests/fixtures/missing.txt").output().unwrap();
+
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("missing.txt"));
+
+    let usage = std::process::Command::new(env!("CARGO_BIN_EXE_day5")).arg("--part").output().unwrap();
+    assert_eq!(usage.status.code(), Some(2));
+
+    let solved = std::process::Command::new(env!("CARGO_BIN_EXE_day5")).arg("./tests/fixtures/example.txt").output().unwrap();
+    assert!(solved.status.success());
+}
+
+#[test]
+fn timing_a_solve() {
+    let (seeds, almanac) = create_conversion_table(Path::new("./tests/fixtures/example.txt")).unwrap();
+    let (answer, time) = day5::cli::timed(|| day5::lowest_location_ranges(&day5::seed_ranges(&seeds), &almanac));
+
+    assert_eq!(answer, 46);
+    assert!(time > std::time::Duration::ZERO);
+    assert!(parse_args(args(&["--time"])).unwrap().time);
+}
+
+#[test]
+fn validate_flag_on_example() {
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_day5")).args(["--validate", "./tests/fixtures/example.txt"]).output().unwrap();
+
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "No issues found\n");
+}
+
+#[test]
+fn strategy_flag_picks_the_solver() {
+    assert_eq!(parse_args(args(&[])).unwrap().strategy, Strategy::Interval);
+    assert_eq!(parse_args(args(&["--strategy", "brute-force"])).unwrap().strategy, Strategy::BruteForce);
+    assert_eq!(parse_args(args(&["--strategy", "scan"])).unwrap().strategy, Strategy::Scan);
+    assert!(parse_args(args(&["--strategy", "guess"])).is_err());
+}
@@ -0,0 +1,19 @@
+// End-to-end check against the worked example from the AoC 2023 Day 5 puzzle text
+
+use day5::{lowest_location, lowest_location_ranges, parse_almanac_str, seed_ranges};
+
+const EXAMPLE: &str = include_str!("fixtures/example.txt");
+
+#[test]
+fn part_one_is_35() {
+    let (seeds, almanac) = parse_almanac_str(EXAMPLE).unwrap();
+
+    assert_eq!(lowest_location(&seeds, &almanac), 35);
+}
+
+#[test]
+fn part_two_is_46() {
+    let (seeds, almanac) = parse_almanac_str(EXAMPLE).unwrap();
+
+    assert_eq!(lowest_location_ranges(&seed_ranges(&seeds), &almanac), 46);
+}
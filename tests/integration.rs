@@ -0,0 +1,1131 @@
+use std::{collections::BTreeMap, io::Cursor, path::Path};
+
+use day5::*;
+
+#[test]
+fn parses_light_to_temperature_key() {
+    let (_seeds, almanac) = create_conversion_table(Path::new("./day5.txt")).unwrap();
+
+    assert!(almanac.maps().contains_key(&(Resource::Light(0), Resource::Temperature(0))));
+    assert!(!almanac.maps().contains_key(&(Resource::Water(0), Resource::Temperature(0))));
+}
+
+#[test]
+fn solves_part_one_example() {
+    let (seeds, almanac) = create_conversion_table(Path::new("./tests/fixtures/example.txt")).unwrap();
+
+    assert_eq!(lowest_location(&seeds, &almanac), 35);
+}
+
+#[test]
+fn rejects_truncated_mapping_line() {
+    let input = "seeds: 79 14\n\nseed-to-soil map:\n52 50 48\n50 98\n";
+    let result = parse_almanac(Cursor::new(input));
+
+    assert!(matches!(result, Err(AlmanacError::BadMapping { line: 5, .. })));
+}
+
+#[test]
+fn rejects_mapping_line_with_tokens_that_arent_numbers() {
+    // A typo'd number, and a line that would have three numbers without its stray tokens
+    for line in ["50 98 2x 7", "50 x 98 2", "50 98 2 junk"] {
+        let input = format!("seeds: 79\n\nseed-to-soil map:\n{}\n", line);
+
+        assert!(matches!(parse_almanac_str(&input), Err(AlmanacError::BadMapping { line: 4, content }) if content == line));
+        assert_eq!(parse_almanac_str_lenient(&input).unwrap().2, vec![format!("Malformed mapping on line 4: {:?}", line)]);
+    }
+}
+
+#[test]
+fn unreadable_line_fails_instead_of_ending_the_input() {
+    let mut input = std::fs::read("./tests/fixtures/example.txt").unwrap();
+    let comment = b"# not utf-8: \xff\n";
+    let second_map = input.windows(20).position(|window| window == b"soil-to-fertilizer m").unwrap();
+    input.splice(second_map..second_map, comment.iter().copied());
+
+    assert!(matches!(parse_almanac(Cursor::new(input)), Err(AlmanacError::FileOpen(_))));
+}
+
+#[test]
+fn solves_part_two_example() {
+    let (seeds, almanac) = create_conversion_table(Path::new("./tests/fixtures/example.txt")).unwrap();
+    assert_eq!(lowest_location_ranges(&seed_ranges(&seeds), &almanac), 46);
+}
+
+#[test]
+fn convert_resource_reports_missing_map() {
+    let (_seeds, almanac) = create_conversion_table(Path::new("./tests/fixtures/example.txt")).unwrap();
+
+    assert_eq!(convert_resource(Resource::Location(46), &almanac), Ok(Resource::Humidity(46)));
+    assert_eq!(convert_resource(Resource::Location(46), &Almanac::new()), Err(Resource::Location(46)));
+}
+
+#[test]
+fn binary_search_matches_linear_scan() {
+    // Fifty 7-wide destination ranges with a 3-wide gap after each, listed out of order
+    let mut input = String::from("seeds: 1\n\nseed-to-soil map:\n");
+    for i in (0..50).rev() {
+        input.push_str(&format!("{} {} 7\n", i * 10, 1000 + i * 7));
+    }
+
+    let (_seeds, almanac) = parse_almanac(Cursor::new(input)).unwrap();
+    let mappings = &almanac.maps()[&(Resource::Seed(0), Resource::Soil(0))];
+
+    for n in 0..520 {
+        let linear = mappings.iter()
+                             .find(|m| m.dest_start <= n && n < m.dest_start + m.range)
+                             .map_or(n, |m| m.src_start + (n - m.dest_start));
+
+        assert_eq!(convert_resource(Resource::Soil(n), &almanac), Ok(Resource::Seed(linear)));
+    }
+}
+
+#[test]
+fn parses_single_range_map_from_str() {
+    let (seeds, almanac) = parse_almanac_str("seeds: 79 14\n\nseed-to-soil map:\n50 98 2\n").unwrap();
+
+    assert_eq!(seeds, vec![79, 14]);
+    assert_eq!(almanac.maps().len(), 1);
+    assert_eq!(almanac.maps()[&(Resource::Seed(0), Resource::Soil(0))].len(), 1);
+}
+
+#[test]
+fn parses_crlf_line_endings() {
+    let (seeds, almanac) = parse_almanac_str("seeds: 79 14\r\n\r\nseed-to-soil map:\r\n50 98 2\r\n").unwrap();
+
+    assert_eq!(seeds, vec![79, 14]);
+    assert_eq!(almanac.maps()[&(Resource::Seed(0), Resource::Soil(0))].len(), 1);
+}
+
+#[test]
+fn parses_tab_separated_numbers() {
+    let (tab_seeds, tab_almanac) = parse_almanac_str("seeds:\t79\t14\n\nseed-to-soil map:\n50\t98\t2\n").unwrap();
+    let (space_seeds, space_almanac) = parse_almanac_str("seeds: 79 14\n\nseed-to-soil map:\n50 98 2\n").unwrap();
+
+    assert_eq!(tab_seeds, space_seeds);
+    assert_eq!(tab_almanac, space_almanac);
+}
+
+#[test]
+fn conversion_path_visits_every_stage() {
+    let (_seeds, almanac) = create_conversion_table(Path::new("./tests/fixtures/example.txt")).unwrap();
+    let path = conversion_path(79, &almanac);
+
+    assert_eq!(path.len(), 8);
+    assert_eq!(path[0], Resource::Seed(79));
+    assert_eq!(path[7], Resource::Location(82));
+}
+
+#[test]
+fn rejects_unknown_map_header() {
+    let result = parse_almanac_str("seeds: 79 14\n\nseed-to-soyl map:\n50 98 2\n");
+
+    assert!(matches!(result, Err(AlmanacError::UnknownMap(header)) if header == "seed-to-soyl map:"));
+}
+
+#[test]
+fn range_arithmetic_does_not_overflow() {
+    let mapping = FarmMapping { dest_start: Num::MAX - 1, src_start: 0, range: 10 };
+    let almanac = Almanac::try_from(ConversionTable::from([((Resource::Seed(0), Resource::Soil(0)), vec![mapping])])).unwrap();
+
+    assert_eq!(convert_resource_forward(Resource::Seed(5), &almanac), Resource::Soil(Num::MAX));
+    assert_eq!(convert_resource(Resource::Soil(Num::MAX - 1), &almanac), Ok(Resource::Seed(0)));
+    assert_eq!(lowest_location_ranges(&[(0, 10), (Num::MAX - 5, 10)], &almanac), Num::MAX - 5);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn saved_almanac_loads_back_identically() {
+    let (_seeds, almanac) = create_conversion_table(Path::new("./tests/fixtures/example.txt")).unwrap();
+    let path = std::env::temp_dir().join("day5_saved_almanac.json");
+
+    save_almanac(&almanac, &path).unwrap();
+    let loaded = load_almanac(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(loaded, almanac);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn saved_almanac_keeps_allow_negative() {
+    let options = ParseOptions { allow_negative: true, ..ParseOptions::default() };
+    let (_seeds, almanac) = parse_almanac_str_with("seeds: -10\n\nseed-to-soil map:\n-20 -12 6\n", &options).unwrap();
+    let path = std::env::temp_dir().join("day5_saved_negative_almanac.json");
+
+    save_almanac(&almanac, &path).unwrap();
+    let loaded = load_almanac(&path).unwrap();
+    assert!(loaded.allows_negative());
+    assert_eq!(loaded, almanac);
+
+    #[cfg(feature = "bincode")]
+    {
+        save_almanac_bin(&almanac, &path).unwrap();
+        assert!(load_almanac_bin(&path).unwrap().allows_negative());
+    }
+
+    // Naming one map twice fails instead of keeping whichever came last
+    std::fs::write(&path, r#"{"allow_negative":false,"maps":[{"src":{"Seed":0},"dest":{"Soil":0},"mappings":[]},{"src":{"Seed":0},"dest":{"Soil":0},"mappings":[]}]}"#).unwrap();
+    let error = load_almanac(&path).unwrap_err();
+    assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+    assert_eq!(error.to_string(), "Map \"seed-to-soil\" given more than once");
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[cfg(feature = "bincode")]
+#[test]
+fn binary_almanac_round_trip() {
+    let (_seeds, almanac) = create_conversion_table(Path::new("./tests/fixtures/example.txt")).unwrap();
+    let path = std::env::temp_dir().join("day5_saved_almanac.bin");
+
+    save_almanac_bin(&almanac, &path).unwrap();
+    let loaded = load_almanac_bin(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(loaded, almanac);
+}
+
+#[test]
+fn seven_steps_from_seed_reach_location() {
+    let mut resource = Resource::Seed(0);
+    for _ in 0..7 {
+        resource = to_next_resource(resource, None);
+    }
+
+    assert_eq!(resource, Resource::Location(0));
+    assert_eq!(to_previous_resource(Resource::Soil(3), Some(9)), Resource::Seed(9));
+}
+
+#[test]
+fn seed_for_lowest_part_two_location_is_in_a_seed_range() {
+    let (seeds, almanac) = create_conversion_table(Path::new("./tests/fixtures/example.txt")).unwrap();
+    let seed = seed_for_location(46, &almanac).unwrap();
+
+    assert!(seeds.chunks_exact(2).any(|pair| pair[0] <= seed && seed < pair[0] + pair[1]));
+    assert_eq!(conversion_path(seed, &almanac)[7], Resource::Location(46));
+}
+
+#[test]
+fn validation_catches_overlapping_source_ranges() {
+    let (_seeds, almanac) = create_conversion_table(Path::new("./tests/fixtures/example.txt")).unwrap();
+    assert_eq!(validate_almanac(&almanac), Ok(()));
+
+    let first = FarmMapping { dest_start: 50, src_start: 98, range: 2 };
+    let second = FarmMapping { dest_start: 0, src_start: 90, range: 9 };
+    let overlapping = Almanac::try_from(ConversionTable::from([((Resource::Seed(0), Resource::Soil(0)), vec![first, second])])).unwrap();
+
+    assert_eq!(validate_almanac(&overlapping), Err((second, first)));
+}
+
+#[test]
+fn whitespace_only_lines_separate_sections() {
+    let input = std::fs::read_to_string("./tests/fixtures/example.txt").unwrap();
+    let padded = input.replace("\n\n", "\n   \n");
+
+    assert_ne!(padded, input);
+    assert_eq!(parse_almanac_str(&padded).unwrap(), parse_almanac_str(&input).unwrap());
+}
+
+#[test]
+fn built_almanac_matches_parsed_one() {
+    let (_seeds, parsed) = parse_almanac_str("seeds: 79\n\nseed-to-soil map:\n50 98 2\n52 50 48\n").unwrap();
+    let built = AlmanacBuilder::new().map(Resource::Seed, Resource::Soil, &[(50, 98, 2), (52, 50, 48)]).build().unwrap();
+
+    assert_eq!(built, parsed);
+    assert_eq!(location_for_seed(79, &built), location_for_seed(79, &parsed));
+
+    let duplicate = AlmanacBuilder::new().map(Resource::Seed, Resource::Soil, &[(50, 98, 2)])
+                                         .map(Resource::Seed, Resource::Soil, &[(52, 50, 48)])
+                                         .build();
+    assert!(matches!(duplicate, Err(AlmanacError::DuplicateMap(_))));
+
+    let skipping = AlmanacBuilder::new().map(Resource::Seed, Resource::Water, &[(50, 98, 2)]).build();
+    assert!(matches!(skipping, Err(AlmanacError::UnknownMap(_))));
+}
+
+#[test]
+fn map_given_twice_is_rejected() {
+    let input = "seeds: 79
+
+seed-to-soil map:
+50 98 2
+
+seed-to-soil map:
+52 50 48
+";
+
+    assert!(matches!(parse_almanac_str(input), Err(AlmanacError::DuplicateMap(header)) if header == "seed-to-soil"));
+    assert!(matches!(solve_streaming(Cursor::new(input)), Err(AlmanacError::DuplicateMap(header)) if header == "seed-to-soil"));
+    assert!(matches!(parse_almanac_parts(&["seeds: 79\nseed-to-soil map:\n50 98 2\n", "seed-to-soil map:\n"], &ParseOptions::default()), Err(AlmanacError::InPart { part: 1, .. })));
+}
+
+#[test]
+fn example_stats() {
+    let (_seeds, almanac) = create_conversion_table(Path::new("./tests/fixtures/example.txt")).unwrap();
+
+    assert_eq!(almanac.stats(), AlmanacStats { maps: 7, total_ranges: 18 });
+}
+
+#[test]
+fn almanac_try_from_str() {
+    let input = std::fs::read_to_string("./tests/fixtures/example.txt").unwrap();
+    let almanac: Almanac = input.as_str().try_into().unwrap();
+
+    assert_eq!(almanac.lowest_location(&[79, 14, 55, 13]), 35);
+
+    let broken: Result<Almanac, AlmanacError> = "seed-to-soil map:\n50 98\n".try_into();
+    assert!(broken.is_err());
+}
+
+#[test]
+fn seed_has_no_previous_resource() {
+    assert_eq!(try_to_previous_resource(Resource::Seed(79), None), None);
+    assert_eq!(try_to_previous_resource(Resource::Soil(81), Some(79)), Some(Resource::Seed(79)));
+}
+
+#[test]
+fn location_scan_agrees_with_interval_solver() {
+    let (seeds, almanac) = create_conversion_table(Path::new("./tests/fixtures/example.txt")).unwrap();
+    let ranges = seed_ranges(&seeds);
+
+    assert_eq!(lowest_location_by_scan(&ranges, &almanac), 46);
+    assert_eq!(lowest_location_by_scan(&ranges, &almanac), lowest_location_ranges(&ranges, &almanac));
+    assert_eq!(lowest_location_by_scan(&[], &almanac), Num::MAX);
+
+    // Location 0 back-converts to seed 10 rather than the seed 0 that passes through to it, so the scan misses, but
+    // it stops once there's nothing higher to reach
+    let many_to_one = AlmanacBuilder::new().map(Resource::Seed, Resource::Soil, &[(0, 10, 5)]).build().unwrap();
+    assert_eq!(lowest_location_ranges(&[(0, 1)], &many_to_one), 0);
+    assert_eq!(lowest_location_by_scan(&[(0, 1)], &many_to_one), Num::MAX);
+}
+
+#[test]
+fn seeds_line_can_come_after_the_maps() {
+    let (seeds, almanac) = parse_almanac_str("seed-to-soil map:\n50 98 2\n52 50 48\n\nseeds: 79 14\n").unwrap();
+
+    assert_eq!(seeds, vec![79, 14]);
+    assert_eq!(almanac.convert_forward(Resource::Seed(79)), Resource::Soil(81));
+    assert!(matches!(parse_almanac_str("seed-to-soil map:\n50 98 2\nseeds: none\n"), Err(AlmanacError::BadSeeds { line: 3, .. })));
+}
+
+#[test]
+fn journey_names_every_stage() {
+    let (_seeds, almanac) = create_conversion_table(Path::new("./tests/fixtures/example.txt")).unwrap();
+    let journey = convert_all_stages(79, &almanac);
+
+    assert_eq!(journey.location, 82);
+    assert_eq!(journey, SeedJourney { seed: 79, soil: 81, fertilizer: 81, water: 81, light: 74, temperature: 78, humidity: 78, location: 82 });
+}
+
+#[test]
+fn reports_missing_maps() {
+    let input = std::fs::read_to_string("./tests/fixtures/example.txt").unwrap();
+    let (_seeds, complete) = parse_almanac_str(&input).unwrap();
+    let (_seeds, truncated) = parse_almanac_str(&input[..input.find("humidity-to-location map:").unwrap()]).unwrap();
+
+    assert_eq!(complete.assert_complete(), Ok(()));
+    assert_eq!(truncated.assert_complete(), Err(vec![(Resource::Humidity(0), Resource::Location(0))]));
+}
+
+#[test]
+fn streaming_solver_matches_in_memory() {
+    let input = std::fs::read_to_string("./tests/fixtures/example.txt").unwrap();
+
+    assert_eq!(solve_streaming(Cursor::new(&input)).unwrap(), 35);
+
+    // Maps in reverse order, which the first pass has to index before anything can be converted
+    let mut sections = input.split("\n\n").collect::<Vec<&str>>();
+    sections[1..].reverse();
+    assert_eq!(solve_streaming(Cursor::new(sections.join("\n\n"))).unwrap(), 35);
+
+    assert!(matches!(solve_streaming(Cursor::new("seeds: 1\n\nseed-to-soil map:\n1 2\n")), Err(AlmanacError::BadMapping { line: 4, .. })));
+    assert!(matches!(solve_streaming(Cursor::new("seed-to-soil map:\n1 2 3\n\nseeds: none\n")), Err(AlmanacError::BadSeeds { line: 4, .. })));
+}
+
+#[test]
+fn forward_and_backward_conversion_are_inverses() {
+    let almanac = AlmanacBuilder::new().map(Resource::Seed, Resource::Soil, &[(50, 98, 2), (52, 50, 48)]).build().unwrap();
+
+    for seed in [0, 49, 50, 79, 97, 98, 99, 100] {
+        let soil = convert(Resource::Seed(seed), &almanac, Direction::Forward).unwrap();
+
+        assert!(matches!(soil, Resource::Soil(_)));
+        assert_eq!(convert(soil, &almanac, Direction::Backward), Ok(Resource::Seed(seed)));
+    }
+
+    assert_eq!(convert(Resource::Seed(79), &almanac, Direction::Forward), Ok(Resource::Soil(81)));
+}
+
+#[test]
+fn backward_conversion_rejects_negative_results() {
+    // Soil 10..20 comes from seeds -5..5, half of which can't exist
+    let almanac = AlmanacBuilder::new().map(Resource::Seed, Resource::Soil, &[(10, -5, 10)]).build().unwrap();
+
+    assert_eq!(convert_resource(Resource::Soil(12), &almanac), Err(Resource::Soil(12)));
+    assert_eq!(convert_resource(Resource::Soil(15), &almanac), Ok(Resource::Seed(0)));
+    assert_eq!(convert_resource(Resource::Soil(30), &almanac), Ok(Resource::Seed(30)));
+
+    // Every backward path applies the same check, and only a missing map passes through
+    let prepared = PreparedAlmanac::new(&almanac);
+    for soil in [12, 15, 30] {
+        assert_eq!(prepared.convert_backward(Resource::Soil(soil)), convert_resource(Resource::Soil(soil), &almanac));
+        assert_eq!(almanac.convert_backward(Resource::Soil(soil)), convert_resource(Resource::Soil(soil), &almanac));
+    }
+    assert_eq!(almanac.convert_backward(Resource::Fertilizer(3)), Ok(Resource::Soil(3)));
+    assert_eq!(convert(Resource::Soil(12), &almanac, Direction::Backward), Err(Resource::Soil(12)));
+    assert_eq!(seed_for_location(12, &almanac), None);
+    assert_eq!(seed_for_location(15, &almanac), Some(0));
+}
+
+#[test]
+fn negative_numbers_convert_when_allowed() {
+    let input = "seeds: -10 5 3 4\n\nseed-to-soil map:\n-20 -12 6\n";
+    let options = ParseOptions { allow_negative: true, ..ParseOptions::default() };
+    let (seeds, almanac) = parse_almanac_str_with(input, &options).unwrap();
+
+    assert_eq!(seeds, vec![-10, 5, 3, 4]);
+    assert_eq!(almanac.validate_all().err().map(|issues| issues.len()), Some(6));        // Only the missing maps
+    assert_eq!(convert_resource_forward(Resource::Seed(-10), &almanac), Resource::Soil(-18));
+    assert_eq!(convert_resource(Resource::Soil(-18), &almanac), Ok(Resource::Seed(-10)));
+    assert_eq!(almanac.lowest_location(&seeds), -18);
+
+    // Seeds -10..-5 and 3..7: -10..=-7 land on -18..=-15, -6 passes through
+    let ranges = seed_ranges(&seeds);
+    let expanded = SeedRangeIter::new(&ranges).map(|seed| location_for_seed(seed, &almanac)).min().unwrap();
+    assert_eq!(lowest_location_ranges(&ranges, &almanac), -18);
+    assert_eq!(expanded, -18);
+    assert_eq!(lowest_location_by_scan(&ranges, &almanac), -18);
+
+    // The scan starts low enough to finish even when every seed is negative
+    assert_eq!(lowest_location_by_scan(&[(-6, 1)], &almanac), -6);
+
+    // Without the flag the same numbers are suspect
+    let (_seeds, strict) = parse_almanac_str(input).unwrap();
+    assert_eq!(convert_resource(Resource::Soil(-18), &strict), Err(Resource::Soil(-18)));
+    assert!(matches!(strict.validate_all(), Err(issues) if issues.contains(&ValidationIssue::Negative(Resource::Seed(0), FarmMapping { dest_start: -20, src_start: -12, range: 6 }))));
+}
+
+#[test]
+fn converts_through_a_single_map() {
+    let (_seeds, almanac) = create_conversion_table(Path::new("./tests/fixtures/example.txt")).unwrap();
+
+    assert_eq!(convert_one(79, Resource::Seed(0), Resource::Soil(0), &almanac), 81);
+    assert_eq!(convert_one(81, Resource::Water(0), Resource::Light(0), &almanac), 74);
+    assert_eq!(convert_one(79, Resource::Seed(0), Resource::Water(0), &almanac), 79);
+}
+
+#[test]
+fn offset_within_a_single_map() {
+    let (_seeds, almanac) = create_conversion_table(Path::new("./tests/fixtures/example.txt")).unwrap();
+
+    // 79 falls in "52 50 48"
+    assert_eq!(offset_at(79, Resource::Seed(0), Resource::Soil(0), &almanac), 2);
+    assert_eq!(offset_at(98, Resource::Seed(0), Resource::Soil(0), &almanac), -48);
+    assert_eq!(offset_at(10, Resource::Seed(0), Resource::Soil(0), &almanac), 0);
+}
+
+#[test]
+fn streaming_minimum_only_reports_improvements() {
+    let (_seeds, almanac) = create_conversion_table(Path::new("./tests/fixtures/example.txt")).unwrap();
+    let mut improvements = Vec::new();
+
+    // Locations 86, 82, 43, 82, 35 and 86
+    let lowest = lowest_location_streaming(&[55, 79, 14, 79, 13, 55], &almanac, |seed, location| improvements.push((seed, location)));
+
+    assert_eq!(lowest, 35);
+    assert_eq!(improvements, vec![(55, 86), (79, 82), (14, 43), (13, 35)]);
+}
+
+#[test]
+fn inverted_almanac_converts_backwards() {
+    let (_seeds, almanac) = create_conversion_table(Path::new("./tests/fixtures/example.txt")).unwrap();
+    let inverted = almanac.inverted();
+
+    for stage in &RESOURCE_ORDER[1..] {
+        for n in 0..110 {
+            let resource = stage(n);
+
+            assert_eq!(inverted.convert_forward(resource), convert_resource(resource, &almanac));
+        }
+    }
+
+    assert_eq!(inverted.convert_forward(Resource::Soil(81)), Ok(Resource::Seed(79)));
+    assert!(inverted.maps().contains_key(&(Resource::Soil(0), Resource::Seed(0))));
+    assert_eq!(inverted.inverted(), almanac);
+
+    // Negative results are rejected the same way as on the original
+    let negative = AlmanacBuilder::new().map(Resource::Seed, Resource::Soil, &[(10, -5, 10)]).build().unwrap();
+    assert_eq!(negative.inverted().convert_forward(Resource::Soil(12)), Err(Resource::Soil(12)));
+}
+
+#[test]
+fn reports_the_winning_seed() {
+    let (seeds, almanac) = create_conversion_table(Path::new("./tests/fixtures/example.txt")).unwrap();
+
+    assert_eq!(lowest_location_with_seed(&seeds, &almanac), Some((13, 35)));
+    assert_eq!(lowest_location_with_seed(&[], &almanac), None);
+}
+
+#[test]
+fn seed_range_iter_expands_lazily() {
+    assert_eq!(SeedRangeIter::new(&[(79, 3), (55, 2)]).collect::<Vec<Num>>(), vec![79, 80, 81, 55, 56]);
+
+    // Empty ranges contribute nothing, wherever they are
+    assert_eq!(SeedRangeIter::new(&[]).count(), 0);
+    assert_eq!(SeedRangeIter::new(&[(5, 0), (10, 1), (20, 0)]).collect::<Vec<Num>>(), vec![10]);
+
+    // A range claiming to run past the top of Num stops at Num::MAX
+    assert_eq!(SeedRangeIter::new(&[(Num::MAX - 1, 10)]).collect::<Vec<Num>>(), vec![Num::MAX - 1, Num::MAX]);
+    assert_eq!(SeedRangeIter::new(&[(Num::MAX, Num::MAX), (0, 1)]).collect::<Vec<Num>>(), vec![Num::MAX, 0]);
+}
+
+#[test]
+fn lowest_value_at_any_stage() {
+    let (seeds, almanac) = create_conversion_table(Path::new("./tests/fixtures/example.txt")).unwrap();
+
+    // Soils 81, 14, 57 and 13
+    assert_eq!(lowest_resource(&seeds, &almanac, Resource::Soil), 13);
+    assert_eq!(lowest_resource(&seeds, &almanac, Resource::Seed), 13);
+    assert_eq!(lowest_resource(&seeds, &almanac, Resource::Location), lowest_location(&seeds, &almanac));
+}
+
+#[test]
+fn seeds_keyword_is_configurable() {
+    let input = "initial: 79 14\n\nseed-to-soil map:\n52 50 48\n";
+    let options = ParseOptions { seeds_keyword: "initial:", strict: true, ..ParseOptions::default() };
+
+    let (seeds, almanac) = parse_almanac_str_with(input, &options).unwrap();
+    assert_eq!(seeds, vec![79, 14]);
+    assert_eq!(convert_resource_forward(Resource::Seed(79), &almanac), Resource::Soil(81));
+
+    // The default keyword leaves "initial:" as a bad seed
+    assert!(matches!(parse_almanac_str_strict(input), Err(AlmanacError::BadSeeds { line: 1, content }) if content == "initial:"));
+}
+
+#[test]
+fn custom_seeds_keyword_reaches_every_reader_and_writer() {
+    // A keyword only known at runtime, as if read from the command line
+    let keyword = String::from("initial:");
+    let options = ParseOptions { seeds_keyword: &keyword, range_format: RangeFormat::End, ..ParseOptions::default() };
+    let (seeds, almanac) = create_conversion_table(Path::new("./tests/fixtures/example.txt")).unwrap();
+
+    let mut text = Vec::new();
+    write_almanac_with(&mut text, &seeds, &almanac, &options).unwrap();
+    let text = String::from_utf8(text).unwrap();
+    assert!(text.starts_with("initial: 79 14 55 13\n\nseed-to-soil map:\n50 98 99\n"));
+
+    assert_eq!(parse_almanac_str_with(&text, &options).unwrap(), (seeds, almanac));
+    assert_eq!(solve_streaming_with(Cursor::new(&text), &options).unwrap(), 35);
+}
+
+#[test]
+fn validate_all_reports_every_issue() {
+    let input = std::fs::read_to_string("./tests/fixtures/example.txt").unwrap();
+    let (_seeds, almanac) = parse_almanac_str(&input).unwrap();
+    assert_eq!(almanac.validate_all(), Ok(()));
+
+    // No humidity-to-location map, and the first two seed-to-soil mappings overlap
+    let broken = input[..input.find("humidity-to-location map:").unwrap()].replace("50 98 2\n", "50 90 20\n");
+    let (_seeds, almanac) = parse_almanac_str(&broken).unwrap();
+
+    assert_eq!(almanac.validate_all(), Err(vec![
+        ValidationIssue::MissingMap(Resource::Humidity(0), Resource::Location(0)),
+        ValidationIssue::Overlap(Resource::Seed(0), FarmMapping { dest_start: 52, src_start: 50, range: 48 }, FarmMapping { dest_start: 50, src_start: 90, range: 20 })
+    ]));
+
+    // The first mapping overlaps both of the others, which don't overlap each other
+    let (wide, inner, later) = (FarmMapping { dest_start: 1000, src_start: 0, range: 100 }, FarmMapping { dest_start: 2000, src_start: 10, range: 1 }, FarmMapping { dest_start: 3000, src_start: 50, range: 1 });
+    let almanac = AlmanacBuilder::new().map(Resource::Seed, Resource::Soil, &[(1000, 0, 100), (2000, 10, 1), (3000, 50, 1)]).build().unwrap();
+    let overlaps = almanac.validate_all()
+                          .unwrap_err()
+                          .into_iter()
+                          .filter(|issue| matches!(issue, ValidationIssue::Overlap(..)))
+                          .collect::<Vec<ValidationIssue>>();
+
+    assert_eq!(overlaps, vec![ValidationIssue::Overlap(Resource::Seed(0), wide, inner), ValidationIssue::Overlap(Resource::Seed(0), wide, later)]);
+}
+
+#[test]
+fn sorts_example_locations() {
+    let (seeds, almanac) = create_conversion_table(Path::new("./tests/fixtures/example.txt")).unwrap();
+    let locations = sorted_locations(&seeds, &almanac);
+
+    assert_eq!(locations, vec![35, 43, 82, 86]);
+    assert_eq!(locations[0], lowest_location(&seeds, &almanac));
+}
+
+#[test]
+fn interval_intersection() {
+    // Disjoint, either way round
+    assert_eq!(intersect((0, 10), (20, 5)), None);
+    assert_eq!(intersect((20, 5), (0, 10)), None);
+
+    // Touching: [0, 10) and [10, 15) share nothing
+    assert_eq!(intersect((0, 10), (10, 5)), None);
+    assert_eq!(intersect((10, 5), (0, 10)), None);
+
+    // Nested
+    assert_eq!(intersect((0, 100), (10, 5)), Some((10, 5)));
+    assert_eq!(intersect((10, 5), (0, 100)), Some((10, 5)));
+    assert_eq!(intersect((10, 5), (10, 5)), Some((10, 5)));
+
+    // Overlapping
+    assert_eq!(intersect((0, 10), (5, 10)), Some((5, 5)));
+    assert_eq!(intersect((5, 10), (0, 10)), Some((5, 5)));
+
+    // Empty intervals never overlap anything
+    assert_eq!(intersect((5, 0), (0, 10)), None);
+
+    // Ends beyond Num::MAX clamp instead of overflowing
+    assert_eq!(intersect((Num::MAX - 5, 100), (Num::MAX - 10, 100)), Some((Num::MAX - 5, 5)));
+}
+
+#[test]
+fn finds_gaps_in_source_coverage() {
+    let mappings = [FarmMapping { dest_start: 0, src_start: 30, range: 10 }, FarmMapping { dest_start: 100, src_start: 10, range: 10 }];
+
+    // 0..10 and 20..30 pass through; nothing past 40 is reported
+    assert_eq!(source_gaps(&mappings), vec![(0, 10), (20, 10)]);
+
+    let (_seeds, almanac) = create_conversion_table(Path::new("./tests/fixtures/example.txt")).unwrap();
+    let seed_to_soil = find_mappings_for_src_resource(&Resource::Seed(0), &almanac).unwrap();
+    assert_eq!(source_gaps(seed_to_soil), vec![(0, 50)]);
+    assert_eq!(source_gaps(&[]), vec![]);
+
+    // Mappings below 0 move the start down with them
+    let negative = [FarmMapping { dest_start: 0, src_start: 5, range: 5 }, FarmMapping { dest_start: 0, src_start: -12, range: 6 }];
+    assert_eq!(source_gaps(&negative), vec![(-6, 11)]);
+}
+
+#[test]
+fn histogram_buckets_locations() {
+    let (seeds, almanac) = create_conversion_table(Path::new("./tests/fixtures/example.txt")).unwrap();
+
+    // Locations 82, 43, 86 and 35
+    assert_eq!(location_histogram(&seeds, &almanac, 10), BTreeMap::from([(30, 1), (40, 1), (80, 2)]));
+    assert_eq!(location_histogram(&seeds, &almanac, 100), BTreeMap::from([(0, 4)]));
+}
+
+#[test]
+fn comment_lines_are_ignored() {
+    let input = std::fs::read_to_string("./tests/fixtures/example.txt").unwrap();
+    let commented = format!("# AoC 2023 day 5 example\n{}", input.replace(" map:\n", " map:\n  # dest src range\n"));
+
+    assert_eq!(parse_almanac_str(&commented).unwrap(), parse_almanac_str(&input).unwrap());
+}
+
+#[test]
+fn resources_sort_by_stage_then_number() {
+    let mut locations = vec![Resource::Location(82), Resource::Location(43), Resource::Location(86), Resource::Location(35)];
+    locations.sort();
+    assert_eq!(locations, vec![Resource::Location(35), Resource::Location(43), Resource::Location(82), Resource::Location(86)]);
+
+    // Any Seed comes before any Soil, whatever the numbers
+    let mut mixed = vec![Resource::Soil(1), Resource::Location(0), Resource::Seed(100), Resource::Soil(0)];
+    mixed.sort();
+    assert_eq!(mixed, vec![Resource::Seed(100), Resource::Soil(0), Resource::Soil(1), Resource::Location(0)]);
+}
+
+#[test]
+fn display_formats() {
+    assert_eq!(Resource::Soil(81).to_string(), "soil 81");
+    assert_eq!(Resource::Temperature(0).to_string(), "temperature 0");
+    assert_eq!(FarmMapping { dest_start: 50, src_start: 98, range: 2 }.to_string(), "50 98 2");
+}
+
+#[test]
+fn strict_parsing_rejects_bad_seeds() {
+    let input = "seeds: 79 1x4 55\n\nseed-to-soil map:\n50 98 2\n";
+
+    assert_eq!(parse_almanac_str(input).unwrap().0, vec![79, 55]);
+    assert!(matches!(parse_almanac_str_strict(input), Err(AlmanacError::BadSeeds { line: 1, content }) if content == "1x4"));
+    assert!(matches!(parse_almanac_str_strict("# seeds below\nseeds: 79 1x4\n"), Err(AlmanacError::BadSeeds { line: 2, content }) if content == "1x4"));
+    assert_eq!(parse_almanac_str_strict("seeds: 79 14 55\n").unwrap().0, vec![79, 14, 55]);
+}
+
+#[test]
+fn inclusive_end_range_format() {
+    let by_length = parse_almanac_str("seeds: 79\n\nseed-to-soil map:\n50 98 2\n52 50 48\n").unwrap();
+    let by_end = parse_almanac_str_with("seeds: 79\n\nseed-to-soil map:\n50 98 99\n52 50 97\n", &ParseOptions { range_format: RangeFormat::End, ..ParseOptions::default() }).unwrap();
+
+    assert_eq!(by_end, by_length);
+    assert_eq!("end".parse::<RangeFormat>(), Ok(RangeFormat::End));
+
+    // An end before its start is malformed rather than a negative range
+    let options = ParseOptions { range_format: RangeFormat::End, ..ParseOptions::default() };
+    let backwards = "seeds: 79\n\nseed-to-soil map:\n50 98 10\n";
+    assert!(matches!(parse_almanac_str_with(backwards, &options), Err(AlmanacError::BadMapping { line: 4, .. })));
+    assert_eq!(parse_almanac_str_with("seeds: 79\n\nseed-to-soil map:\n50 98 98\n", &options).unwrap().1.stats(), AlmanacStats { maps: 1, total_ranges: 1 });
+
+    let empty = AlmanacBuilder::new().map(Resource::Seed, Resource::Soil, &[(50, 98, 0)]).build().unwrap();
+    assert_eq!(write_almanac_with(&mut Vec::new(), &[79], &empty, &options).unwrap_err().kind(), std::io::ErrorKind::InvalidInput);
+    assert!("inclusive".parse::<RangeFormat>().is_err());
+}
+
+#[test]
+fn parses_almanac_split_across_parts() {
+    let input = std::fs::read_to_string("./tests/fixtures/example.txt").unwrap();
+    let (seeds_line, maps) = input.split_once("\n\n").unwrap();
+
+    assert_eq!(parse_almanac_parts(&[seeds_line, maps], &ParseOptions::default()).unwrap(), parse_almanac_str(&input).unwrap());
+
+    // The seeds can come last too, since a labelled seeds line is read wherever it is
+    let (seeds, almanac) = parse_almanac_parts(&[maps, seeds_line], &ParseOptions::default()).unwrap();
+    assert_eq!(seeds, vec![79, 14, 55, 13]);
+    assert_eq!(almanac.lowest_location(&seeds), 35);
+}
+
+#[test]
+fn each_part_is_parsed_as_its_own_file() {
+    let input = std::fs::read_to_string("./tests/fixtures/example.txt").unwrap();
+    let (seeds_line, maps) = input.split_once("\n\n").unwrap();
+
+    // Every part may start with its own byte-order mark
+    let marked = format!("\u{FEFF}{}", seeds_line);
+    assert_eq!(parse_almanac_parts(&[marked.as_str(), maps], &ParseOptions::default()).unwrap(), parse_almanac_str(&input).unwrap());
+
+    // Errors give the line within the part they're in, and which part that was
+    let corrupt = maps.replace("0 15 37\n", "0 15\n");
+    let error = parse_almanac_parts(&[seeds_line, &corrupt], &ParseOptions::default()).unwrap_err();
+    assert!(matches!(&error, AlmanacError::InPart { part: 1, error } if matches!(**error, AlmanacError::BadMapping { line: 6, .. })));
+    assert_eq!(error.to_string(), "In input 2: Malformed mapping on line 6: \"0 15\"");
+
+    let missing = create_conversion_table_from_paths(&["./tests/fixtures/example.txt", "./tests/fixtures/missing.txt"], &ParseOptions::default());
+    assert!(matches!(missing, Err(AlmanacError::InPart { part: 1, error }) if matches!(*error, AlmanacError::FileOpen(_))));
+}
+
+#[test]
+fn lenient_parsing_skips_bad_mapping_lines() {
+    let input = std::fs::read_to_string("./tests/fixtures/example.txt").unwrap();
+    let corrupt = input.replace("0 15 37\n", "0 15\n");
+
+    assert!(matches!(parse_almanac_str(&corrupt), Err(AlmanacError::BadMapping { line: 8, .. })));
+
+    let (seeds, almanac, warnings) = parse_almanac_str_lenient(&corrupt).unwrap();
+    assert_eq!(seeds, vec![79, 14, 55, 13]);
+    assert_eq!(almanac.stats(), AlmanacStats { maps: 7, total_ranges: 17 });
+    assert_eq!(warnings, vec![String::from("Malformed mapping on line 8: \"0 15\"")]);
+
+    let (_seeds, _almanac, warnings) = parse_almanac_str_lenient(&input).unwrap();
+    assert!(warnings.is_empty());
+}
+
+#[test]
+fn finds_seeds_that_miss_every_mapping() {
+    let (seeds, almanac) = parse_almanac_str("seeds: 5 60\n\nseed-to-soil map:\n50 98 2\n52 50 48\n").unwrap();
+
+    assert_eq!(seeds_hitting_no_mapping(&seeds, &almanac), vec![5]);
+}
+
+#[cfg(feature = "gzip")]
+#[test]
+fn parses_gzip_compressed_almanac() {
+    use std::io::Write;
+
+    let input = std::fs::read_to_string("./tests/fixtures/example.txt").unwrap();
+    let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+    encoder.write_all(input.as_bytes()).unwrap();
+
+    let path = std::env::temp_dir().join("day5_example.txt.gz");
+    std::fs::write(&path, encoder.finish().unwrap()).unwrap();
+    let compressed = create_conversion_table(&path);
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(compressed.unwrap(), parse_almanac_str(&input).unwrap());
+}
+
+#[test]
+fn rejects_almanac_without_seeds() {
+    let result = parse_almanac_str("seed-to-soil map:\n50 98 2\n52 50 48\n");
+
+    assert!(matches!(result, Err(AlmanacError::NoSeeds)));
+}
+
+#[test]
+fn almanac_methods_convert_both_ways() {
+    let (seeds, almanac) = create_conversion_table(Path::new("./tests/fixtures/example.txt")).unwrap();
+
+    assert_eq!(almanac.convert_forward(Resource::Seed(79)), Resource::Soil(81));
+    assert_eq!(almanac.convert_backward(Resource::Soil(81)), Ok(Resource::Seed(79)));
+    assert_eq!(almanac.lowest_location(&seeds), 35);
+}
+
+#[test]
+fn optimize_drops_only_identity_mappings() {
+    let real = FarmMapping { dest_start: 50, src_start: 98, range: 2 };
+    let identity = FarmMapping { dest_start: 10, src_start: 10, range: 20 };
+    let mut almanac = Almanac::try_from(ConversionTable::from([((Resource::Seed(0), Resource::Soil(0)), vec![identity, real])])).unwrap();
+    let before = (0..110).map(|n| almanac.convert_forward(Resource::Seed(n))).collect::<Vec<Resource>>();
+
+    almanac.optimize();
+
+    assert_eq!(almanac.maps()[&(Resource::Seed(0), Resource::Soil(0))], vec![real]);
+    assert_eq!((0..110).map(|n| almanac.convert_forward(Resource::Seed(n))).collect::<Vec<Resource>>(), before);
+}
+
+#[test]
+fn dot_output_has_an_edge_per_map() {
+    let (_seeds, almanac) = create_conversion_table(Path::new("./tests/fixtures/example.txt")).unwrap();
+    let dot = to_dot(&almanac);
+
+    assert!(dot.contains("\"Seed\" -> \"Soil\""));
+    assert_eq!(dot.matches(" -> ").count(), 7);
+}
+
+#[test]
+fn lists_maps_in_chain_order() {
+    let (_seeds, almanac) = create_conversion_table(Path::new("./tests/fixtures/example.txt")).unwrap();
+    let sources = almanac.maps_in_chain_order()
+                         .into_iter()
+                         .map(|((src, _dest), _mappings)| *src)
+                         .collect::<Vec<Resource>>();
+
+    assert_eq!(sources, RESOURCE_ORDER[..7].iter().map(|stage| stage(0)).collect::<Vec<Resource>>());
+}
+
+#[test]
+fn written_almanac_parses_back_the_same() {
+    let (seeds, almanac) = create_conversion_table(Path::new("./tests/fixtures/example.txt")).unwrap();
+
+    let mut text = Vec::new();
+    write_almanac(&mut text, &seeds, &almanac).unwrap();
+    let text = String::from_utf8(text).unwrap();
+    assert!(text.starts_with("seeds: 79 14 55 13\n\nseed-to-soil map:\n"));
+
+    assert_eq!(parse_almanac_str(&text).unwrap(), (seeds, almanac));
+}
+
+#[test]
+fn solve_all_maps_each_seed_to_its_location() {
+    let (seeds, almanac) = create_conversion_table(Path::new("./tests/fixtures/example.txt")).unwrap();
+    let locations = solve_all(&seeds, &almanac);
+
+    assert_eq!(locations.len(), 4);
+    assert_eq!(locations[&79], 82);
+    assert_eq!(locations[&14], 43);
+}
+
+#[test]
+fn empty_sections_still_register_their_map() {
+    let input = std::fs::read_to_string("./tests/fixtures/example.txt").unwrap();
+    let start = input.find("light-to-temperature map:").unwrap();
+    let end = input.find("temperature-to-humidity map:").unwrap();
+    let emptied = format!("{}light-to-temperature map:\n\n{}", &input[..start], &input[end..]);
+
+    let (_seeds, almanac) = parse_almanac_str(&emptied).unwrap();
+    assert_eq!(almanac.maps().get(&(Resource::Light(0), Resource::Temperature(0))), Some(&Vec::new()));
+    assert_eq!(almanac.assert_complete(), Ok(()));
+    assert_eq!(almanac.convert_forward(Resource::Light(77)), Resource::Temperature(77));
+}
+
+#[test]
+fn every_solver_agrees_on_example() {
+    let (seeds, almanac) = create_conversion_table(Path::new("./tests/fixtures/example.txt")).unwrap();
+    let solvers: [&dyn Solver; 3] = [&BruteForceSolver, &IntervalSolver, &LocationScanSolver];
+
+    for solver in solvers {
+        assert_eq!(solver.solve(&seed_ranges(&seeds), &almanac), 46);
+    }
+}
+
+#[test]
+fn counts_seeds_in_part_two_ranges() {
+    let (seeds, _almanac) = create_conversion_table(Path::new("./tests/fixtures/example.txt")).unwrap();
+
+    assert_eq!(total_seeds(&seed_ranges(&seeds)), 27);
+    assert_eq!(total_seeds(&[(0, Num::MAX), (5, Num::MAX), (1, -3)]), 2 * Num::MAX as u128);
+}
+
+#[test]
+fn convert_many_keeps_order_and_duplicates() {
+    let (_seeds, almanac) = create_conversion_table(Path::new("./tests/fixtures/example.txt")).unwrap();
+
+    assert_eq!(convert_many(&[79, 79, 14], &almanac), vec![82, 82, 43]);
+}
+
+#[test]
+fn ignores_a_byte_order_mark() {
+    let input = std::fs::read_to_string("./tests/fixtures/example.txt").unwrap();
+    let (seeds, almanac) = parse_almanac_str_strict(&format!("\u{FEFF}{}", input)).unwrap();
+
+    assert_eq!(seeds, vec![79, 14, 55, 13]);
+    assert_eq!((seeds, almanac), parse_almanac_str(&input).unwrap());
+
+    // Streaming too, whether the mark sticks to the seeds line or to a map header
+    let strict = ParseOptions { strict: true, ..ParseOptions::default() };
+    let (seeds_line, maps) = input.split_once("\n\n").unwrap();
+    assert_eq!(solve_streaming_with(Cursor::new(format!("\u{FEFF}{}", input)), &strict).unwrap(), 35);
+    assert_eq!(solve_streaming(Cursor::new(format!("\u{FEFF}{}\n\n{}\n", maps, seeds_line))).unwrap(), 35);
+}
+
+#[test]
+fn parses_seeds_header_without_space() {
+    let (seeds, _almanac) = parse_almanac_str("seeds:79 14 55 13\n\nseed-to-soil map:\n50 98 2\n").unwrap();
+
+    assert_eq!(seeds, vec![79, 14, 55, 13]);
+}
+
+#[test]
+fn prepared_almanac_matches_unprepared_conversion() {
+    let (seeds, almanac) = create_conversion_table(Path::new("./tests/fixtures/example.txt")).unwrap();
+    let prepared = PreparedAlmanac::new(&almanac);
+
+    for seed in 0..100 {
+        assert_eq!(prepared.location_for_seed(seed), location_for_seed(seed, &almanac));
+        assert_eq!(prepared.convert_backward(Resource::Location(seed)), convert_resource(Resource::Location(seed), &almanac));
+    }
+    assert_eq!(seeds.iter().map(|seed| prepared.location_for_seed(*seed)).min(), Some(35));
+}
+
+#[test]
+fn solves_with_map_sections_in_any_order() {
+    let input = std::fs::read_to_string("./tests/fixtures/example.txt").unwrap();
+    let mut sections = input.split("\n\n").collect::<Vec<&str>>();
+    sections[1..].reverse();
+    sections.swap(2, 5);
+
+    let (seeds, almanac) = parse_almanac_str(&sections.join("\n\n")).unwrap();
+
+    assert_eq!(lowest_location(&seeds, &almanac), 35);
+    assert_eq!(lowest_location_ranges(&[(79, 14), (55, 13)], &almanac), 46);
+}
+
+#[cfg(feature = "i128")]
+#[test]
+fn converts_numbers_beyond_i64() {
+    let beyond = i64::MAX as Num * 4;
+    let input = format!("seeds: {} 10\n\nseed-to-soil map:\n{} {} 100\n", beyond + 5, beyond * 2, beyond);
+    let (seeds, almanac) = parse_almanac_str(&input).unwrap();
+
+    assert_eq!(seeds, vec![beyond + 5, 10]);
+    assert_eq!(location_for_seed(beyond + 5, &almanac), beyond * 2 + 5);
+    assert_eq!(lowest_location_ranges(&[(beyond, 10)], &almanac), beyond * 2);
+}
+
+#[test]
+fn parses_resource_names() {
+    assert_eq!("fertilizer".parse::<Resource>(), Ok(Resource::Fertilizer(0)));
+    assert!("banana".parse::<Resource>().is_err());
+}
+
+#[test]
+fn map_coverage_tags_pieces_with_their_mapping() {
+    let mappings = [FarmMapping { dest_start: 50, src_start: 98, range: 2 }, FarmMapping { dest_start: 52, src_start: 50, range: 48 }];
+
+    // 40..60 is half below the second mapping; 95..105 hits the end of the second, all of the first, then runs off
+    assert_eq!(map_coverage((40, 20), &mappings), vec![((52, 10), Some(1)), ((40, 10), None)]);
+    assert_eq!(map_coverage((95, 10), &mappings), vec![((50, 2), Some(0)), ((97, 3), Some(1)), ((100, 5), None)]);
+}
+
+#[test]
+fn parses_final_mapping_line_without_trailing_newline() {
+    let input = std::fs::read_to_string("./tests/fixtures/example.txt").unwrap();
+    let input = input.trim_end();
+    assert!(input.ends_with("56 93 4"));
+
+    let key = (Resource::Humidity(0), Resource::Location(0));
+    let (_seeds, from_str) = parse_almanac_str(input).unwrap();
+    let (_seeds, from_reader) = parse_almanac(Cursor::new(input)).unwrap();
+
+    assert_eq!(from_str.maps()[&key].len(), 2);
+    assert_eq!(from_reader.maps()[&key].len(), 2);
+}
+
+#[test]
+fn with_num_keeps_the_variant() {
+    assert_eq!(with_num(Resource::Water(5), 9), Resource::Water(9));
+
+    let mut resource = Resource::Light(1);
+    *get_resource_num_mut(&mut resource) += 2;
+    assert_eq!(resource, Resource::Light(3));
+}
+
+#[test]
+fn coalesce_merges_contiguous_translations() {
+    let first = FarmMapping { dest_start: 10, src_start: 0, range: 5 };
+    let second = FarmMapping { dest_start: 15, src_start: 5, range: 5 };
+    let unrelated = FarmMapping { dest_start: 100, src_start: 10, range: 5 };
+    let mut almanac = Almanac::try_from(ConversionTable::from([((Resource::Seed(0), Resource::Soil(0)), vec![second, unrelated, first])])).unwrap();
+    let before = (0..30).map(|n| almanac.convert_forward(Resource::Seed(n))).collect::<Vec<Resource>>();
+
+    almanac.coalesce();
+
+    assert_eq!(almanac.maps()[&(Resource::Seed(0), Resource::Soil(0))], vec![FarmMapping { dest_start: 10, src_start: 0, range: 10 }, unrelated]);
+    assert_eq!((0..30).map(|n| almanac.convert_forward(Resource::Seed(n))).collect::<Vec<Resource>>(), before);
+}
+
+#[test]
+fn coalesce_drops_duplicates() {
+    let mapping = FarmMapping { dest_start: 10, src_start: 0, range: 5 };
+    let mut almanac = Almanac::try_from(ConversionTable::from([((Resource::Seed(0), Resource::Soil(0)), vec![mapping, FarmMapping { dest_start: 50, src_start: 0, range: 5 }, mapping])])).unwrap();
+
+    almanac.coalesce();
+
+    assert_eq!(almanac.maps()[&(Resource::Seed(0), Resource::Soil(0))], vec![mapping, FarmMapping { dest_start: 50, src_start: 0, range: 5 }]);
+}
+
+#[test]
+fn coalesce_leaves_apart_ranges_too_long_to_merge() {
+    let (whole, rest) = (FarmMapping { dest_start: 0, src_start: 0, range: Num::MAX }, FarmMapping { dest_start: Num::MAX, src_start: Num::MAX, range: 5 });
+    let mut almanac = Almanac::try_from(ConversionTable::from([((Resource::Seed(0), Resource::Soil(0)), vec![rest, whole])])).unwrap();
+
+    almanac.coalesce();
+
+    assert_eq!(almanac.maps()[&(Resource::Seed(0), Resource::Soil(0))], vec![whole, rest]);
+}
+
+#[cfg(feature = "rayon")]
+#[test]
+fn bruteforce_progress_fires_every_interval() {
+    let (_seeds, almanac) = create_conversion_table(Path::new("./tests/fixtures/example.txt")).unwrap();
+    let mut reports = Vec::new();
+
+    let lowest = lowest_location_ranges_bruteforce_with_progress(&[(79, 14), (55, 13)], &almanac, 10, |processed| reports.push(processed));
+
+    assert_eq!(lowest, 46);
+    assert_eq!(reports, vec![10, 20]);
+}
+
+#[test]
+fn parses_seed_line_as_ranges() {
+    assert_eq!(parse_seed_ranges("seeds: 79 14 55 13"), vec![(79, 14), (55, 13)]);
+}
+
+#[test]
+fn lookup_rejects_two_maps_for_one_destination() {
+    let mapping = FarmMapping { dest_start: 50, src_start: 98, range: 2 };
+
+    // Two maps into Soil can't be built in the first place, so no conversion can quietly pick one
+    let into_soil = Almanac::try_from(ConversionTable::from([
+        ((Resource::Seed(0), Resource::Soil(0)), vec![mapping]),
+        ((Resource::Water(0), Resource::Soil(0)), vec![mapping])
+    ]));
+    assert!(matches!(into_soil, Err(AlmanacError::UnknownMap(header)) if header == "water-to-soil"));
+
+    // Keys only differ in their numbers, which don't count
+    let twice = Almanac::try_from(ConversionTable::from([
+        ((Resource::Seed(0), Resource::Soil(0)), vec![mapping]),
+        ((Resource::Seed(5), Resource::Soil(0)), vec![])
+    ]));
+    assert!(matches!(twice, Err(AlmanacError::DuplicateMap(header)) if header == "seed-to-soil"));
+
+    let almanac = Almanac::try_from(ConversionTable::from([((Resource::Seed(3), Resource::Soil(0)), vec![mapping])])).unwrap();
+    assert_eq!(find_mappings_for_dest_resource(&Resource::Soil(0), &almanac), Ok(&vec![mapping]));
+    assert_eq!(find_mappings_for_dest_resource(&Resource::Light(0), &almanac), Err(LookupError::NotFound));
+    assert_eq!(find_mappings_for_src_resource(&Resource::Seed(0), &almanac), Ok(&vec![mapping]));
+    assert_eq!(almanac.convert_forward(Resource::Seed(98)), Resource::Soil(50));
+}
+
+#[test]
+fn explained_conversion_reports_the_mapping_used() {
+    let (_seeds, almanac) = create_conversion_table(Path::new("./tests/fixtures/example.txt")).unwrap();
+    let seed_to_soil = &almanac.maps()[&(Resource::Seed(0), Resource::Soil(0))];
+
+    let (soil, mapping) = convert_forward_explained(Resource::Seed(79), &almanac);
+
+    assert_eq!(soil, Resource::Soil(81));
+    assert_eq!(seed_to_soil[mapping.unwrap()], FarmMapping { dest_start: 52, src_start: 50, range: 48 });
+    assert_eq!(convert_forward_explained(Resource::Seed(14), &almanac), (Resource::Soil(14), None));
+}
+
+#[cfg(feature = "http")]
+#[test]
+fn parses_almanac_from_url() {
+    use std::{io::{Read, Write}, net::TcpListener, thread};
+
+    // A one-shot HTTP server handing back the example input
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/day5.txt", listener.local_addr().unwrap());
+    let body = std::fs::read_to_string("./tests/fixtures/example.txt").unwrap();
+    let expected = parse_almanac_str(&body).unwrap();
+
+    let server = thread::spawn(move || {
+        let (mut stream, _addr) = listener.accept().unwrap();
+        let _request = stream.read(&mut [0; 1024]).unwrap();
+        write!(stream, "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}", body.len(), body).unwrap();
+    });
+
+    assert_eq!(parse_almanac_url(&url).unwrap(), expected);
+    server.join().unwrap();
+
+    assert!(matches!(parse_almanac_url("http://127.0.0.1:1/day5.txt"), Err(AlmanacError::Fetch(_))));
+}
+
+#[test]
+fn farm_mapping_boundaries() {
+    // 10 source numbers starting at 50, sent to 100..110
+    let mapping = FarmMapping { dest_start: 100, src_start: 50, range: 10 };
+
+    assert!(!mapping.contains_src(49));
+    assert!(mapping.contains_src(50));
+    assert!(mapping.contains_src(59));
+    assert!(!mapping.contains_src(60));
+    assert_eq!(mapping.apply_forward(50), 100);
+    assert_eq!(mapping.apply_forward(59), 109);
+
+    assert!(!mapping.contains_dest(99));
+    assert!(mapping.contains_dest(100));
+    assert!(mapping.contains_dest(109));
+    assert!(!mapping.contains_dest(110));
+    assert_eq!(mapping.apply_backward(100), 50);
+    assert_eq!(mapping.apply_backward(109), 59);
+
+    // Ranges that only touch share nothing
+    assert!(mapping.overlaps_src(&FarmMapping { dest_start: 0, src_start: 59, range: 5 }));
+    assert!(!mapping.overlaps_src(&FarmMapping { dest_start: 0, src_start: 60, range: 5 }));
+    assert!(!mapping.overlaps_src(&FarmMapping { dest_start: 0, src_start: 45, range: 5 }));
+    assert!(!mapping.overlaps_src(&FarmMapping { dest_start: 0, src_start: 55, range: 0 }));
+
+    // Conversion in both directions agrees on the same endpoints
+    let almanac = AlmanacBuilder::new().map(Resource::Seed, Resource::Soil, &[(100, 50, 10)]).build().unwrap();
+    for (seed, soil) in [(49, 49), (50, 100), (59, 109), (60, 60)] {
+        assert_eq!(convert_resource_forward(Resource::Seed(seed), &almanac), Resource::Soil(soil));
+    }
+    for (soil, seed) in [(99, 99), (100, 50), (109, 59), (110, 110)] {
+        assert_eq!(convert_resource(Resource::Soil(soil), &almanac), Ok(Resource::Seed(seed)));
+    }
+}
+
+#[test]
+fn default_chain_solves_example() {
+    let input = std::fs::read_to_string("./tests/fixtures/example.txt").unwrap();
+    let (seeds, almanac) = chain::ChainAlmanac::parse_str(&input, chain::Chain::default()).unwrap();
+
+    assert_eq!(almanac.lowest(&seeds), 35);
+
+    let (_seeds, fixed) = parse_almanac_str(&input).unwrap();
+    assert_eq!(chain::ChainAlmanac::from(&fixed), almanac);
+}
+
+#[test]
+fn custom_chain_with_three_stages() {
+    let stages = ["ore", "ingot", "gear"].map(String::from).to_vec();
+    let input = "seeds: 1 20\n\nore-to-ingot map:\n10 0 5\n\ningot-to-gear map:\n100 10 5\n";
+    let (seeds, almanac) = chain::ChainAlmanac::parse_str(input, chain::Chain::new(stages.clone())).unwrap();
+
+    // 1 -> 11 -> 101, while 20 misses both maps
+    assert_eq!(almanac.convert_to_last(1), 101);
+    assert_eq!(almanac.lowest(&seeds), 20);
+
+    let skipping = "seeds: 1\n\nore-to-gear map:\n10 0 5\n";
+    assert!(matches!(chain::ChainAlmanac::parse_str(skipping, chain::Chain::new(stages)), Err(AlmanacError::UnknownMap(_))));
+}
+
+#[test]
+fn chain_parser_follows_the_almanac_rules() {
+    let input = std::fs::read_to_string("./tests/fixtures/example.txt").unwrap();
+    let (seeds_line, maps) = input.split_once("\n\n").unwrap();
+
+    // Comments, a byte-order mark and the seeds line coming last all parse as they do for an Almanac
+    let reordered = format!("\u{FEFF}# c\n{}\n\n{}\n", maps, seeds_line);
+    let (seeds, almanac) = chain::ChainAlmanac::parse_str(&reordered, chain::Chain::default()).unwrap();
+    assert_eq!(almanac.lowest(&seeds), 35);
+
+    let (_seeds, fixed) = parse_almanac_str(&reordered).unwrap();
+    assert_eq!(chain::ChainAlmanac::from(&fixed), almanac);
+
+    // As do the parser options
+    let options = ParseOptions { seeds_keyword: "initial:", range_format: RangeFormat::End, ..ParseOptions::default() };
+    let (seeds, almanac) = chain::ChainAlmanac::parse_str_with("initial: 1\n\nseed-to-soil map:\n10 0 4\n", chain::Chain::default(), &options).unwrap();
+    assert_eq!(almanac.convert_forward(chain::Resource { stage: 0, num: seeds[0] }), chain::Resource { stage: 1, num: 11 });
+}
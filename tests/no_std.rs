@@ -0,0 +1,16 @@
+use std::{env, process::Command};
+
+// The conversion core has to keep building with std switched off. A separate target directory stops this from
+// invalidating the main build.
+#[test]
+fn core_builds_without_std() {
+    let target_dir = env::temp_dir().join("day5_no_std_target");
+    let status = Command::new(env!("CARGO"))
+                         .args(["build", "--lib", "--no-default-features", "--quiet"])
+                         .arg("--manifest-path").arg(concat!(env!("CARGO_MANIFEST_DIR"), "/Cargo.toml"))
+                         .arg("--target-dir").arg(&target_dir)
+                         .status()
+                         .unwrap();
+
+    assert!(status.success());
+}
@@ -0,0 +1,52 @@
+use proptest::prelude::*;
+
+use day5::{location_for_seed, seed_for_location, Almanac, ConversionTable, FarmMapping, Num, RESOURCE_ORDER};
+
+// A map that permutes equally sized, evenly spaced slots among themselves. Every number is then covered by exactly
+// one source and one destination range (or by neither), so the map is a bijection and can be walked backwards.
+fn permuting_map() -> impl Strategy<Value = Vec<FarmMapping>> {
+    (1..10usize, 1..100 as Num, 0..50 as Num).prop_flat_map(|(slots, width, gap)| {
+        let starts = (0..slots as Num).map(|slot| slot * (width + gap)).collect::<Vec<Num>>();
+
+        Just(starts.clone()).prop_shuffle().prop_map(move |dests| {
+            starts.iter()
+                  .zip(dests)
+                  .map(|(src_start, dest_start)| FarmMapping { dest_start, src_start: *src_start, range: width })
+                  .collect()
+        })
+    })
+}
+
+fn permuting_almanac() -> impl Strategy<Value = Almanac> {
+    prop::collection::vec(permuting_map(), 7).prop_map(|maps| {
+        let maps = maps.into_iter()
+                       .enumerate()
+                       .map(|(stage, mappings)| ((RESOURCE_ORDER[stage](0), RESOURCE_ORDER[stage + 1](0)), mappings))
+                       .collect::<ConversionTable>();
+
+        Almanac::try_from(maps).unwrap()
+    })
+}
+
+proptest! {
+    #[test]
+    fn forward_then_backward_returns_the_seed(almanac in permuting_almanac(), seed in 0..2000 as Num) {
+        let location = location_for_seed(seed, &almanac);
+
+        prop_assert_eq!(seed_for_location(location, &almanac), Some(seed));
+    }
+}
+
+#[cfg(feature = "random")]
+proptest! {
+    #[test]
+    fn random_almanacs_are_well_formed_and_reversible(rng_seed in any::<u64>(), ranges in 1..20usize, seed in 0..20_000 as Num) {
+        use rand::{rngs::StdRng, SeedableRng};
+
+        let almanac = day5::random_almanac(&mut StdRng::seed_from_u64(rng_seed), ranges);
+
+        prop_assert_eq!(almanac.stats().maps, 7);
+        prop_assert_eq!(day5::validate_almanac(&almanac), Ok(()));
+        prop_assert_eq!(seed_for_location(location_for_seed(seed, &almanac), &almanac), Some(seed));
+    }
+}
@@ -0,0 +1,60 @@
+use std::{alloc::{GlobalAlloc, Layout, System}, fs::File, io::BufReader, path::Path, sync::atomic::{AtomicUsize, Ordering}};
+
+use day5::{create_conversion_table, lowest_location, solve_streaming};
+
+// Tracks the most memory held at once, so the two solvers' footprints can be compared. This is the only test in
+// the file, so nothing else allocates while it measures.
+struct PeakAlloc;
+
+static CURRENT: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for PeakAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let current = CURRENT.fetch_add(layout.size(), Ordering::SeqCst) + layout.size();
+        PEAK.fetch_max(current, Ordering::SeqCst);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        CURRENT.fetch_sub(layout.size(), Ordering::SeqCst);
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: PeakAlloc = PeakAlloc;
+
+// Peak memory used by `f`, over and above what was already allocated
+fn peak_during<T>(f: impl FnOnce() -> T) -> (T, usize) {
+    let baseline = CURRENT.load(Ordering::SeqCst);
+    PEAK.store(baseline, Ordering::SeqCst);
+
+    let result = f();
+    (result, PEAK.load(Ordering::SeqCst) - baseline)
+}
+
+#[test]
+fn streaming_uses_a_fraction_of_the_memory() {
+    // 7 maps of 20k ranges each, a few MB of text
+    let mut input = String::from("seeds: 5 123456 999999 1500000\n");
+    for header in ["seed-to-soil", "soil-to-fertilizer", "fertilizer-to-water", "water-to-light", "light-to-temperature", "temperature-to-humidity", "humidity-to-location"] {
+        input.push_str(&format!("\n{} map:\n", header));
+        for i in 0..20_000 {
+            input.push_str(&format!("{} {} 90\n", (i * 7919 % 20_000) * 100, i * 100));
+        }
+    }
+
+    let path = std::env::temp_dir().join("day5_streaming.txt");
+    std::fs::write(&path, input).unwrap();
+
+    let (in_memory, in_memory_peak) = peak_during(|| {
+        let (seeds, almanac) = create_conversion_table(Path::new(&path)).unwrap();
+        lowest_location(&seeds, &almanac)
+    });
+    let (streamed, streamed_peak) = peak_during(|| solve_streaming(BufReader::new(File::open(&path).unwrap())).unwrap());
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(streamed, in_memory);
+    assert!(streamed_peak * 100 < in_memory_peak, "streamed {} bytes, in memory {} bytes", streamed_peak, in_memory_peak);
+}